@@ -0,0 +1,75 @@
+use std::{error::Error, fmt};
+
+use crate::matcher::{Automaton, Match};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calibration {
+    pub line: usize,
+    pub first: Match,
+    pub last: Match,
+}
+
+impl Calibration {
+    pub fn value(&self) -> u32 {
+        self.first.value * 10 + self.last.value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoDigitError {
+    pub line: usize,
+}
+
+impl fmt::Display for NoDigitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No digit on line {}", self.line)
+    }
+}
+
+impl Error for NoDigitError {}
+
+// `line` is 1-based, matching what an editor shows.
+pub fn calibrate_line(
+    line: usize,
+    text: &str,
+    automaton: &Automaton,
+) -> Result<Calibration, NoDigitError> {
+    automaton
+        .first_last(text)
+        .map(|(first, last)| Calibration { line, first, last })
+        .ok_or(NoDigitError { line })
+}
+
+pub fn spans<'a>(
+    data: &'a str,
+    automaton: &'a Automaton,
+) -> impl Iterator<Item = Result<Calibration, NoDigitError>> + 'a {
+    data.lines()
+        .enumerate()
+        .map(|(i, l)| calibrate_line(i + 1, l, automaton))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{TokenKind, Vocabulary};
+
+    #[test]
+    fn spans_test() {
+        let automaton = Vocabulary::english().build();
+        let res: Vec<_> = spans("two1nine\nabc\n7pqrstsixteen", &automaton).collect();
+
+        let line_1 = res[0].as_ref().unwrap();
+        assert_eq!(line_1.value(), 29);
+        assert_eq!((line_1.first.start, line_1.first.end), (0, 3));
+        assert_eq!(line_1.first.kind, TokenKind::Word);
+        assert_eq!((line_1.last.start, line_1.last.end), (4, 8));
+
+        assert_eq!(res[1], Err(NoDigitError { line: 2 }));
+
+        let line_3 = res[2].as_ref().unwrap();
+        assert_eq!(line_3.line, 3);
+        assert_eq!(line_3.first.kind, TokenKind::Numeral);
+        assert_eq!(line_3.value(), 76);
+    }
+}
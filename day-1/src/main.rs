@@ -1,54 +1,64 @@
-use std::{fs, vec};
+pub mod calibration;
+pub mod matcher;
+pub mod stream;
 
-const DIGITS: &[&str] = &[
-    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-];
+use calibration::{spans, NoDigitError};
+use matcher::{Automaton, Vocabulary};
+use std::{
+    env,
+    fs::{self, File},
+    io::BufReader,
+};
+use stream::{proc_stream, BidiMatcher};
 
 fn main() {
-    let data = fs::read_to_string("data/day-1-input.txt").unwrap();
-    let answer_1 = proc(&data, false);
-    println!("Day 1 part one: {answer_1}");
-
-    let answer_2 = proc(&data, true);
-    println!("Day 1 part two: {answer_2}");
-}
+    if let Some(path) = env::args().nth(1) {
+        for (part, words) in [("one", false), ("two", true)] {
+            let reader = BufReader::new(File::open(&path).unwrap());
+            match proc_stream(reader, &BidiMatcher::new(&vocabulary(words))) {
+                Ok(answer) => println!("Day 1 part {part}: {answer}"),
+                Err(e) => println!("Day 1 part {part}: {e}"),
+            }
+        }
+        return;
+    }
 
-fn check_digit(line: &str) -> Option<u32> {
-    line.chars().next().unwrap().to_digit(10)
-}
+    let data = fs::read_to_string("data/day-1-input.txt").unwrap();
+    match proc(&data, &vocabulary(false).build()) {
+        Ok(answer_1) => println!("Day 1 part one: {answer_1}"),
+        Err(e) => println!("Day 1 part one: {e}"),
+    }
 
-fn check_letters(line: &str) -> Option<u32> {
-    for (i, v) in DIGITS.iter().enumerate() {
-        if line.starts_with(v) {
-            return Some((i + 1) as u32);
-        }
+    match proc(&data, &vocabulary(true).build()) {
+        Ok(answer_2) => println!("Day 1 part two: {answer_2}"),
+        Err(e) => println!("Day 1 part two: {e}"),
     }
-    None
 }
 
-fn proc_line(mut line: &str, words: bool) -> u32 {
-    let mut store = vec![];
-    while !line.is_empty() {
-        if let Some(d) = check_digit(line) {
-            store.push(d);
-        } else if words {
-            if let Some(d) = check_letters(line) {
-                store.push(d);
-            }
-        }
-
-        line = &line[1..];
+fn vocabulary(words: bool) -> Vocabulary {
+    if words {
+        Vocabulary::english()
+    } else {
+        Vocabulary::numerals()
     }
-    store.first().unwrap() * 10 + store.last().unwrap()
 }
 
-fn proc(data: &str, letters: bool) -> u32 {
-    data.lines().map(|l| proc_line(l, letters)).sum()
+fn proc(data: &str, automaton: &Automaton) -> Result<u32, NoDigitError> {
+    spans(data, automaton).map(|c| c.map(|c| c.value())).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use calibration::calibrate_line;
+
+    fn calibrate(line: &str, automaton: &Automaton) -> Result<u32, NoDigitError> {
+        calibrate_line(1, line, automaton).map(|c| c.value())
+    }
+
+    fn proc_line(line: &str, words: bool) -> u32 {
+        calibrate(line, &vocabulary(words).build()).unwrap()
+    }
 
     #[test]
     fn line_test() {
@@ -61,7 +71,7 @@ mod tests {
     #[test]
     fn test_file() {
         let data = fs::read_to_string("data/day-1-test.txt").unwrap();
-        assert_eq!(proc(&data, false), 142);
+        assert_eq!(proc(&data, &vocabulary(false).build()), Ok(142));
     }
 
     #[test]
@@ -75,4 +85,23 @@ mod tests {
         assert_eq!(proc_line("7pqrstsixteen", true), 76);
         assert_eq!(proc_line("twoneight", true), 28);
     }
+
+    #[test]
+    fn test_no_digit() {
+        let automaton = vocabulary(true).build();
+        assert_eq!(
+            proc("1abc2\nabc\ntreb7uchet", &automaton),
+            Err(NoDigitError { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let automaton = Vocabulary::english()
+            .with_word("zero", 0)
+            .with_word("uno", 1)
+            .build();
+        assert_eq!(calibrate("zerotwo", &automaton), Ok(2));
+        assert_eq!(calibrate("x9unozer", &automaton), Ok(91));
+    }
 }
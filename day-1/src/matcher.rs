@@ -0,0 +1,285 @@
+use std::collections::VecDeque;
+
+const ENGLISH: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Word,
+    Numeral,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub text: String,
+    pub value: u32,
+    pub kind: TokenKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
+    pub kind: TokenKind,
+}
+
+// Set of tokens recognised as digits. Numerals are always part of it, words
+// are added per language or one by one for aliases.
+#[derive(Debug, Clone)]
+pub struct Vocabulary {
+    patterns: Vec<Pattern>,
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self::numerals()
+    }
+}
+
+impl Vocabulary {
+    pub fn numerals() -> Self {
+        let patterns = (0..10)
+            .map(|d| Pattern {
+                text: d.to_string(),
+                value: d,
+                kind: TokenKind::Numeral,
+            })
+            .collect();
+
+        Vocabulary { patterns }
+    }
+
+    pub fn english() -> Self {
+        Self::numerals().with_words(ENGLISH, 1)
+    }
+
+    // Adds `words` with consecutive values starting at `first`.
+    pub fn with_words(mut self, words: &[&str], first: u32) -> Self {
+        for (i, w) in words.iter().enumerate() {
+            self = self.with_word(w, first + i as u32);
+        }
+        self
+    }
+
+    pub fn with_word(mut self, word: &str, value: u32) -> Self {
+        self.patterns.push(Pattern {
+            text: word.to_string(),
+            value,
+            kind: TokenKind::Word,
+        });
+        self
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    pub fn build(&self) -> Automaton {
+        Automaton::new(&self.patterns)
+    }
+
+    pub fn build_reversed(&self) -> Automaton {
+        Automaton::reversed(&self.patterns)
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.patterns
+            .iter()
+            .map(|p| p.text.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    next: [u32; 256],
+    fail: u32,
+    // Pattern ending exactly at this state.
+    out: u32,
+    // Closest state along the fail chain that has an output.
+    dict: u32,
+}
+
+impl State {
+    fn new() -> Self {
+        State {
+            next: [NONE; 256],
+            fail: 0,
+            out: NONE,
+            dict: NONE,
+        }
+    }
+}
+
+// Aho-Corasick automaton over bytes with all transitions resolved, so scanning
+// is a single table lookup per byte plus one step per reported match.
+#[derive(Debug, Clone)]
+pub struct Automaton {
+    states: Vec<State>,
+    patterns: Vec<Pattern>,
+}
+
+impl Automaton {
+    pub fn new(patterns: &[Pattern]) -> Self {
+        Self::build(patterns, false)
+    }
+
+    // Matches the patterns spelled backwards, for scanning text from the end.
+    pub fn reversed(patterns: &[Pattern]) -> Self {
+        Self::build(patterns, true)
+    }
+
+    fn build(patterns: &[Pattern], reverse: bool) -> Self {
+        let mut states = vec![State::new()];
+
+        for (idx, p) in patterns.iter().enumerate() {
+            let mut bytes = p.text.as_bytes().to_vec();
+            if reverse {
+                bytes.reverse();
+            }
+
+            let mut s = 0;
+            for b in bytes {
+                if states[s].next[b as usize] == NONE {
+                    states[s].next[b as usize] = states.len() as u32;
+                    states.push(State::new());
+                }
+                s = states[s].next[b as usize] as usize;
+            }
+            // Later definitions of the same text win, so aliases can override.
+            states[s].out = idx as u32;
+        }
+
+        let mut queue = VecDeque::new();
+        for b in 0..256 {
+            match states[0].next[b] {
+                NONE => states[0].next[b] = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+
+        while let Some(s) = queue.pop_front() {
+            let fail = states[s].fail as usize;
+            states[s].dict = if states[fail].out != NONE {
+                fail as u32
+            } else {
+                states[fail].dict
+            };
+
+            for b in 0..256 {
+                let fallback = states[fail].next[b];
+                match states[s].next[b] {
+                    NONE => states[s].next[b] = fallback,
+                    child => {
+                        states[child as usize].fail = fallback;
+                        queue.push_back(child as usize);
+                    }
+                }
+            }
+        }
+
+        Automaton {
+            states,
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    pub fn next_state(&self, state: usize, b: u8) -> usize {
+        self.states[state].next[b as usize] as usize
+    }
+
+    // Patterns ending at `state`, longest first.
+    pub fn outputs(&self, state: usize) -> impl Iterator<Item = &Pattern> + '_ {
+        let mut s = if self.states[state].out != NONE {
+            state as u32
+        } else {
+            self.states[state].dict
+        };
+
+        std::iter::from_fn(move || {
+            if s == NONE {
+                return None;
+            }
+            let p = &self.patterns[self.states[s as usize].out as usize];
+            s = self.states[s as usize].dict;
+            Some(p)
+        })
+    }
+
+    // All matches, overlapping ones included, ordered by end position.
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Match> + 'a {
+        let mut state = 0;
+        text.bytes().enumerate().flat_map(move |(i, b)| {
+            state = self.next_state(state, b);
+            self.outputs(state).map(move |p| Match {
+                start: i + 1 - p.text.len(),
+                end: i + 1,
+                value: p.value,
+                kind: p.kind,
+            })
+        })
+    }
+
+    // Matches with the smallest and the largest start offset.
+    pub fn first_last(&self, text: &str) -> Option<(Match, Match)> {
+        let mut ret: Option<(Match, Match)> = None;
+        for m in self.find_iter(text) {
+            ret = Some(match ret {
+                None => (m, m),
+                Some((first, last)) => (
+                    if m.start < first.start { m } else { first },
+                    if m.start >= last.start { m } else { last },
+                ),
+            });
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_test() {
+        let automaton = Vocabulary::english().build();
+        let values: Vec<u32> = automaton.find_iter("twoneight").map(|m| m.value).collect();
+        assert_eq!(values, [2, 1, 8]);
+
+        let (first, last) = automaton.first_last("xtwone3four").unwrap();
+        assert_eq!((first.start, first.end, first.value), (1, 4, 2));
+        assert_eq!((last.start, last.end, last.value), (7, 11, 4));
+        assert_eq!(last.kind, TokenKind::Word);
+
+        assert!(automaton.first_last("abc").is_none());
+    }
+
+    #[test]
+    fn nested_test() {
+        let automaton = Vocabulary::default()
+            .with_word("abcd", 1)
+            .with_word("bc", 2)
+            .build();
+        let (first, last) = automaton.first_last("abcd").unwrap();
+        assert_eq!(first.value, 1);
+        assert_eq!(last.value, 2);
+    }
+
+    #[test]
+    fn vocabulary_test() {
+        let automaton = Vocabulary::numerals()
+            .with_words(&["null", "eins", "zwei", "drei"], 0)
+            .with_word("zero", 0)
+            .build();
+        let values: Vec<u32> = automaton
+            .find_iter("zerozweinull7")
+            .map(|m| m.value)
+            .collect();
+        assert_eq!(values, [0, 2, 0, 7]);
+    }
+}
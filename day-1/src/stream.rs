@@ -0,0 +1,157 @@
+use std::{error::Error, fmt, io::BufRead};
+
+use crate::calibration::NoDigitError;
+use crate::matcher::{Automaton, Match, Vocabulary};
+
+// Finds the first digit with a forward automaton and the last one with an
+// automaton over the reversed patterns, so neither side needs to look at more
+// of the line than it has to.
+#[derive(Debug, Clone)]
+pub struct BidiMatcher {
+    forward: Automaton,
+    backward: Automaton,
+    max_len: usize,
+}
+
+impl BidiMatcher {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        BidiMatcher {
+            forward: vocabulary.build(),
+            backward: vocabulary.build_reversed(),
+            max_len: vocabulary.max_len(),
+        }
+    }
+
+    // A longer pattern may still start earlier than the first match found,
+    // so keep going until no pattern could.
+    pub fn first(&self, line: &[u8]) -> Option<Match> {
+        let mut state = 0;
+        let mut best: Option<Match> = None;
+
+        for (i, &b) in line.iter().enumerate() {
+            if best.is_some_and(|m| i >= m.start + self.max_len) {
+                break;
+            }
+
+            state = self.forward.next_state(state, b);
+            for p in self.forward.outputs(state) {
+                let start = i + 1 - p.text.len();
+                if best.is_none_or(|m| start < m.start) {
+                    best = Some(Match {
+                        start,
+                        end: i + 1,
+                        value: p.value,
+                        kind: p.kind,
+                    });
+                }
+            }
+        }
+
+        best
+    }
+
+    // Scanning backwards, the first match found is the one starting last.
+    pub fn last(&self, line: &[u8]) -> Option<Match> {
+        let mut state = 0;
+
+        for (i, &b) in line.iter().enumerate().rev() {
+            state = self.backward.next_state(state, b);
+            if let Some(p) = self.backward.outputs(state).next() {
+                return Some(Match {
+                    start: i,
+                    end: i + p.text.len(),
+                    value: p.value,
+                    kind: p.kind,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    NoDigit(NoDigitError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{e}"),
+            StreamError::NoDigit(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for StreamError {}
+
+impl From<std::io::Error> for StreamError {
+    fn from(e: std::io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+// Reads one line at a time into a reused buffer, so memory use only depends
+// on the longest line.
+pub fn proc_stream<R: BufRead>(mut reader: R, matcher: &BidiMatcher) -> Result<u64, StreamError> {
+    let mut buf = vec![];
+    let mut sum = 0;
+    let mut line = 0;
+
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(sum);
+        }
+        line += 1;
+
+        let text = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+
+        match (matcher.first(text), matcher.last(text)) {
+            (Some(first), Some(last)) => sum += (first.value * 10 + last.value) as u64,
+            _ => return Err(StreamError::NoDigit(NoDigitError { line })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bidi_test() {
+        let matcher = BidiMatcher::new(&Vocabulary::english());
+        let first = matcher.first(b"xtwone3four").unwrap();
+        assert_eq!((first.start, first.end, first.value), (1, 4, 2));
+        let last = matcher.last(b"xtwone3four").unwrap();
+        assert_eq!((last.start, last.end, last.value), (7, 11, 4));
+
+        assert_eq!(matcher.last(b"twoneight").unwrap().value, 8);
+        assert!(matcher.first(b"abc").is_none());
+
+        let matcher = BidiMatcher::new(
+            &Vocabulary::numerals()
+                .with_word("abcd", 1)
+                .with_word("bc", 2),
+        );
+        assert_eq!(matcher.first(b"abcd").unwrap().value, 1);
+        assert_eq!(matcher.last(b"abcd").unwrap().value, 2);
+    }
+
+    #[test]
+    fn stream_test() {
+        let data = std::fs::read("data/day-1-test.txt").unwrap();
+        let matcher = BidiMatcher::new(&Vocabulary::numerals());
+        assert_eq!(proc_stream(data.as_slice(), &matcher).unwrap(), 142);
+
+        let matcher = BidiMatcher::new(&Vocabulary::english());
+        let data = "two1nine\r\neightwothree\r\nabc\r\n";
+        match proc_stream(data.as_bytes(), &matcher) {
+            Err(StreamError::NoDigit(e)) => assert_eq!(e.line, 3),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use interval_set::IntervalSet;
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, digit1, newline, one_of},
@@ -12,7 +13,7 @@ use std::fs;
 type PartRule<'a> = (&'a str, char, u32, &'a str);
 type PartWorkfloq<'a> = (&'a str, Vec<PartRule<'a>>, &'a str);
 type PartData = (u32, u32, u32, u32);
-type PartRange = ((u32, u32), (u32, u32), (u32, u32), (u32, u32));
+type PartRange = [IntervalSet<u32>; 4];
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
@@ -26,7 +27,7 @@ fn digit1_u32(input: &str) -> IResult<&str, u32> {
     map_res(digit1, |s: &str| s.parse::<u32>())(input)
 }
 
-fn parse_rule(input: &str) -> IResult<&str, PartRule> {
+fn parse_rule(input: &str) -> IResult<&str, PartRule<'_>> {
     let (input, param) = alpha1(input)?;
     let (input, cmp) = one_of("<>")(input)?;
     let (input, value) = digit1_u32(input)?;
@@ -35,7 +36,7 @@ fn parse_rule(input: &str) -> IResult<&str, PartRule> {
     Ok((input, (param, cmp, value, next_rule)))
 }
 
-fn parse_rule_line(input: &str) -> IResult<&str, PartWorkfloq> {
+fn parse_rule_line(input: &str) -> IResult<&str, PartWorkfloq<'_>> {
     let (input, rule) = alpha1(input)?;
     let (input, _) = tag("{")(input)?;
     let (input, data) = separated_list1(tag(","), parse_rule)(input)?;
@@ -60,7 +61,7 @@ fn parse_part_line(input: &str) -> IResult<&str, PartData> {
     Ok((input, (data_x, data_m, data_a, data_s)))
 }
 
-fn parse(input: &str) -> IResult<&str, (Vec<PartWorkfloq>, Vec<PartData>)> {
+fn parse(input: &str) -> IResult<&str, (Vec<PartWorkfloq<'_>>, Vec<PartData>)> {
     let (input, rules) = many1(parse_rule_line)(input)?;
     let (input, _) = newline(input)?;
     let (input, parts) = many1(parse_part_line)(input)?;
@@ -68,7 +69,7 @@ fn parse(input: &str) -> IResult<&str, (Vec<PartWorkfloq>, Vec<PartData>)> {
     Ok((input, (rules, parts)))
 }
 
-fn build_map(rules: Vec<PartWorkfloq>) -> HashMap<String, (Vec<PartRule>, &str)> {
+fn build_map(rules: Vec<PartWorkfloq<'_>>) -> HashMap<String, (Vec<PartRule<'_>>, &str)> {
     let mut ret = HashMap::new();
     for (workflow, part_rule, last_rule) in rules {
         ret.insert(workflow.to_string(), (part_rule, last_rule));
@@ -124,44 +125,48 @@ fn proc_1(data: &str) -> u32 {
     ret
 }
 
-fn extract_range<'a>(range: &'a mut PartRange, param: &str) -> &'a mut (u32, u32) {
+fn extract_range<'a>(range: &'a mut PartRange, param: &str) -> &'a mut IntervalSet<u32> {
     match param {
-        "x" => &mut range.0,
-        "m" => &mut range.1,
-        "a" => &mut range.2,
-        "s" => &mut range.3,
+        "x" => &mut range[0],
+        "m" => &mut range[1],
+        "a" => &mut range[2],
+        "s" => &mut range[3],
         _ => unreachable!(),
     }
 }
 
-fn apply_rule<'a>(mut range: PartRange, rule: &'a (Vec<PartRule>, &str)) -> Vec<(&'a str, PartRange)> {
+// Values of a rating that pass `op value`.
+fn rule_values(op: char, value: u32) -> IntervalSet<u32> {
+    let bound = match op {
+        '<' => value.checked_sub(1).map(|v| (0, v)),
+        '>' => value.checked_add(1).map(|v| (v, u32::MAX)),
+        _ => unreachable!(),
+    };
+    bound.map_or_else(IntervalSet::new, |(lo, hi)| IntervalSet::from_range(lo, hi))
+}
+
+fn apply_rule<'a>(
+    mut range: PartRange,
+    rule: &'a (Vec<PartRule>, &str),
+) -> Vec<(&'a str, PartRange)> {
     let (rules, last_rule) = rule;
     let mut ret = vec![];
 
     for &(param, op, value, workflow) in rules {
+        let passing = rule_values(op, value);
         let part_range = extract_range(&mut range, param);
-        let value = if op == '>' { value + 1 } else { value };
-        let mut range_left = if value > part_range.0 {
-            Some((part_range.0, part_range.1.min(value - 1)))
-        } else {
-            None
-        };
-        let mut range_right = if value < part_range.1 {
-            Some((part_range.0.max(value), part_range.1))
-        } else {
-            None
-        };
-        if op == '>' {
-            std::mem::swap(&mut range_left, &mut range_right);
+        let matched = part_range.intersection(&passing);
+        *part_range = part_range.difference(&passing);
+        let exhausted = part_range.is_empty();
+
+        if !matched.is_empty() {
+            let mut branch = range.clone();
+            *extract_range(&mut branch, param) = matched;
+            ret.push((workflow, branch));
         }
 
-        if let Some(x) = range_left {
-            *extract_range(&mut range, param) = x;
-            ret.push((workflow, range));
-        }
-
-        if let Some(x) = range_right {
-            *extract_range(&mut range, param) = x;
+        if exhausted {
+            return ret;
         }
     }
 
@@ -170,11 +175,8 @@ fn apply_rule<'a>(mut range: PartRange, rule: &'a (Vec<PartRule>, &str)) -> Vec<
     ret
 }
 
-fn mult_range(range: PartRange) -> u64 {
-    (range.0 .1 + 1 - range.0 .0) as u64
-        * (range.1 .1 + 1 - range.1 .0) as u64
-        * (range.2 .1 + 1 - range.2 .0) as u64
-        * (range.3 .1 + 1 - range.3 .0) as u64
+fn mult_range(range: &PartRange) -> u64 {
+    range.iter().map(|r| r.len() as u64).product()
 }
 
 fn proc_2(data: &str) -> u64 {
@@ -183,17 +185,17 @@ fn proc_2(data: &str) -> u64 {
     let map = build_map(rules);
     let mut ret = 0;
 
-    let start = ((1, 4000), (1, 4000), (1, 4000), (1, 4000));
+    let start: PartRange = std::array::from_fn(|_| IntervalSet::from_range(1, 4000));
 
     let mut ranges = vec![("in", start)];
 
     while let Some((workflow, range)) = ranges.pop() {
-        let rule = map.get(&workflow.to_string()).unwrap();
+        let rule = map.get(workflow).unwrap();
         let new_ranges = apply_rule(range, rule);
 
         for (new_workflow, new_range) in new_ranges {
             if new_workflow == "A" {
-                ret += mult_range(new_range);
+                ret += mult_range(&new_range);
             } else if new_workflow == "R" {
             } else {
                 ranges.push((new_workflow, new_range));
@@ -222,6 +224,30 @@ mod test {
         assert_eq!(res, 19114);
     }
 
+    #[test]
+    fn test_apply_rule() {
+        let rule = (vec![("a", '<', 2006, "qkq"), ("m", '>', 2090, "A")], "rfg");
+        let start: PartRange = std::array::from_fn(|_| IntervalSet::from_range(1, 4000));
+        let res = apply_rule(start, &rule);
+
+        let names: Vec<&str> = res.iter().map(|r| r.0).collect();
+        assert_eq!(names, ["qkq", "A", "rfg"]);
+        assert_eq!(res[0].1[2].ranges(), [(1, 2005)]);
+        assert_eq!(res[1].1[1].ranges(), [(2091, 4000)]);
+        assert_eq!(res[2].1[1].ranges(), [(1, 2090)]);
+        assert_eq!(res[2].1[2].ranges(), [(2006, 4000)]);
+        assert_eq!(
+            res.iter().map(|r| mult_range(&r.1)).sum::<u64>(),
+            4000_u64.pow(4)
+        );
+
+        // Every value passes the first rule, so nothing is left for the rest.
+        let rule = (vec![("x", '>', 0, "A"), ("m", '<', 5, "R")], "R");
+        let start: PartRange = std::array::from_fn(|_| IntervalSet::from_range(1, 4000));
+        let res = apply_rule(start, &rule);
+        assert_eq!(res.len(), 1);
+    }
+
     #[test]
     fn test_proc2() {
         let data = fs::read_to_string("data/test.txt").unwrap();
@@ -1,64 +1,109 @@
-use std::str::FromStr;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug)]
-pub enum CubeColor {
-    Red,
-    Green,
-    Blue,
-}
-
-impl FromStr for CubeColor {
-    type Err = anyhow::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "red" => Ok(CubeColor::Red),
-            "green" => Ok(CubeColor::Green),
-            "blue" => Ok(CubeColor::Blue),
-            &_ => Err(anyhow::anyhow!("Bad color")),
-        }
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Game {
     pub id: u32,
     pub sets: Vec<GameSet>,
 }
 
-#[derive(Debug, Default)]
+// Colours keep the order they were first added in, so printing a parsed set
+// reproduces its text. Equality ignores that order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct GameSet {
-    pub red: u32,
-    pub green: u32,
-    pub blue: u32,
+    pub cubes: IndexMap<String, u32>,
+}
+
+// A game without sets prints as "Game 1:", so it reads back differently from
+// a game with one empty set, "Game 1: ".
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (i, set) in self.sets.iter().enumerate() {
+            let sep = if i > 0 { "; " } else { " " };
+            write!(f, "{sep}{set}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GameSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, count)) in self.cubes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{count} {color}")?;
+        }
+        Ok(())
+    }
 }
 
 impl Game {
     pub fn max_set(&self) -> GameSet {
         let mut ret = GameSet::default();
         for set in &self.sets {
-            ret.red = ret.red.max(set.red);
-            ret.green = ret.green.max(set.green);
-            ret.blue = ret.blue.max(set.blue);
+            for (color, &count) in &set.cubes {
+                let c = ret.cubes.entry(color.clone()).or_default();
+                *c = (*c).max(count);
+            }
         }
 
         ret
     }
 
+    // Colours missing from the limit allow no cubes at all.
     pub fn check(&self, set_limit: &GameSet) -> bool {
-        let max_set = self.max_set();
-        max_set.red <= set_limit.red
-            && max_set.green <= set_limit.green
-            && max_set.blue <= set_limit.blue
+        self.max_set()
+            .cubes
+            .iter()
+            .all(|(color, &count)| count <= set_limit.get(color))
     }
 
-    pub fn power(&self) -> u32 {
+    // Product of the fewest cubes of each of `colors` the game needs. A
+    // colour the game never shows needs none, which makes the power 0.
+    pub fn power_over(&self, colors: &[&str]) -> u32 {
         let max_set = self.max_set();
-        max_set.red * max_set.green * max_set.blue
+        colors.iter().map(|c| max_set.get(c)).product()
     }
 }
 
+// Every colour that shows up in `games`, in the order first seen. Used as the
+// colours of the bag when nothing else says which ones it holds.
+pub fn colors(games: &[Game]) -> Vec<&str> {
+    let mut ret: Vec<&str> = vec![];
+    for set in games.iter().flat_map(|g| &g.sets) {
+        for color in set.cubes.keys() {
+            if !ret.contains(&color.as_str()) {
+                ret.push(color);
+            }
+        }
+    }
+
+    ret
+}
+
 impl GameSet {
     pub fn build(red: u32, green: u32, blue: u32) -> Self {
-        GameSet { red, green, blue }
+        Self::from_counts(&[("red", red), ("green", green), ("blue", blue)])
+    }
+
+    pub fn from_counts(counts: &[(&str, u32)]) -> Self {
+        let mut ret = GameSet::default();
+        for &(color, count) in counts {
+            ret.add(color, count);
+        }
+
+        ret
+    }
+
+    pub fn get(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, color: &str, count: u32) {
+        *self.cubes.entry(color.to_string()).or_default() += count;
     }
 }
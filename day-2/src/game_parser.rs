@@ -1,32 +1,49 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, digit1, space1};
-use nom::combinator::map_res;
+use nom::combinator::{map_res, opt};
 use nom::multi::separated_list0;
+use nom::sequence::preceded;
 use nom::IResult;
-use std::str::FromStr;
 
-use crate::game::{CubeColor, Game, GameSet};
+use crate::game::{Game, GameSet};
 
-fn cube_convert(cubes: Vec<(u32, CubeColor)>) -> Result<GameSet> {
-    let mut ret = GameSet::default();
+// Lenient parsing sums repeated colours and ignores whatever follows the last
+// set; strict parsing rejects both, as well as zero counts, empty sets and
+// games without sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    #[default]
+    Lenient,
+    Strict,
+}
+
+type RawSet<'a> = Vec<(u32, &'a str)>;
 
-    for cube in cubes {
-        let color = cube.1;
-        let c = match color {
-            CubeColor::Red => &mut ret.red,
-            CubeColor::Green => &mut ret.green,
-            CubeColor::Blue => &mut ret.blue,
-        };
-        *c += cube.0;
+fn cube_convert(cubes: RawSet<'_>, mode: ParseMode) -> Result<GameSet> {
+    if mode == ParseMode::Strict && cubes.is_empty() {
+        bail!("empty set");
+    }
+
+    let mut ret = GameSet::default();
+    for (count, color) in cubes {
+        if mode == ParseMode::Strict {
+            if count == 0 {
+                bail!("zero count for {color}");
+            }
+            if ret.cubes.contains_key(color) {
+                bail!("duplicate colour {color}");
+            }
+        }
+        ret.add(color, count);
     }
     Ok(ret)
 }
 
-fn cube_parser(input: &str) -> IResult<&str, (u32, CubeColor)> {
+fn cube_parser(input: &str) -> IResult<&str, (u32, &str)> {
     let (input, count) = map_res(digit1, |s: &str| s.parse::<u32>())(input)?;
     let (input, _) = space1(input)?;
-    let (input, color) = map_res(alpha1, CubeColor::from_str)(input)?;
+    let (input, color) = alpha1(input)?;
 
     Ok((input, (count, color)))
 }
@@ -34,24 +51,46 @@ fn cube_parser(input: &str) -> IResult<&str, (u32, CubeColor)> {
 fn game_id_parser(input: &str) -> IResult<&str, u32> {
     let (input, _) = tag("Game ")(input)?;
     let (input, game_id) = map_res(digit1, |s: &str| s.parse::<u32>())(input)?;
-    let (input, _) = tag(": ")(input)?;
+    let (input, _) = tag(":")(input)?;
 
     Ok((input, game_id))
 }
 
-fn game_set_parser(input: &str) -> IResult<&str, GameSet> {
-    map_res(separated_list0(tag(", "), cube_parser), cube_convert)(input)
+fn game_set_parser(input: &str) -> IResult<&str, RawSet<'_>> {
+    separated_list0(tag(", "), cube_parser)(input)
 }
 
-fn game_line(input: &str) -> IResult<&str, (u32, Vec<GameSet>)> {
+fn game_line(input: &str) -> IResult<&str, (u32, Vec<RawSet<'_>>)> {
     let (input, game_id) = game_id_parser(input)?;
-    let (input, data) = separated_list0(tag("; "), game_set_parser)(input)?;
+    let (input, data) = opt(preceded(
+        tag(" "),
+        separated_list0(tag("; "), game_set_parser),
+    ))(input)?;
 
-    Ok((input, (game_id, data)))
+    Ok((input, (game_id, data.unwrap_or_default())))
 }
 
 pub fn parse_game(input: &str) -> Result<Game> {
-    let (_, (id, sets)) = game_line(input).map_err(|e| e.to_owned())?;
+    parse_game_with(input, ParseMode::Lenient)
+}
+
+pub fn parse_game_with(input: &str, mode: ParseMode) -> Result<Game> {
+    let (rest, (id, raw_sets)) = game_line(input).map_err(|e| e.to_owned())?;
+
+    if mode == ParseMode::Strict && !rest.is_empty() {
+        bail!("Game {id}: unexpected trailing input {rest:?}");
+    }
+    if mode == ParseMode::Strict && raw_sets.is_empty() {
+        bail!("Game {id}: no sets");
+    }
+
+    let mut sets = vec![];
+    for (i, cubes) in raw_sets.into_iter().enumerate() {
+        match cube_convert(cubes, mode) {
+            Ok(set) => sets.push(set),
+            Err(e) => bail!("Game {id}, set {}: {e}", i + 1),
+        }
+    }
 
     Ok(Game { id, sets })
 }
@@ -59,6 +98,108 @@ pub fn parse_game(input: &str) -> Result<Game> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::colors;
+    use proptest::prelude::*;
+
+    fn game_strategy() -> impl Strategy<Value = Game> {
+        let set =
+            prop::collection::vec(("[a-z]{1,8}", 0..100u32), 0..5).prop_map(|cubes| GameSet {
+                cubes: cubes.into_iter().collect(),
+            });
+        (0..1000u32, prop::collection::vec(set, 0..6)).prop_map(|(id, sets)| Game { id, sets })
+    }
+
+    // Lines written the way the puzzle input is, without going through
+    // `Display`. A colour appears at most once per set.
+    fn line_strategy() -> impl Strategy<Value = String> {
+        let set = prop::collection::vec(("[a-z]{1,8}", 0..100u32), 0..5).prop_map(|cubes| {
+            let mut colors = vec![];
+            let mut parts = vec![];
+            for (color, count) in cubes {
+                if !colors.contains(&color) {
+                    parts.push(format!("{count} {color}"));
+                    colors.push(color);
+                }
+            }
+            parts.join(", ")
+        });
+        (0..1000u32, prop::collection::vec(set, 0..6)).prop_map(|(id, sets)| match sets.len() {
+            0 => format!("Game {id}:"),
+            _ => format!("Game {id}: {}", sets.join("; ")),
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn text_round_trip(game in game_strategy()) {
+            let text = game.to_string();
+            let parsed = parse_game(&text).unwrap();
+            prop_assert_eq!(parsed.to_string(), text);
+            prop_assert_eq!(parsed, game);
+        }
+
+        #[test]
+        fn line_round_trip(line in line_strategy()) {
+            prop_assert_eq!(parse_game(&line).unwrap().to_string(), line);
+        }
+
+        #[test]
+        fn json_round_trip(game in game_strategy()) {
+            let json = serde_json::to_string(&game).unwrap();
+            let parsed = serde_json::from_str::<Game>(&json).unwrap();
+            prop_assert_eq!(parsed.to_string(), game.to_string());
+            prop_assert_eq!(parsed, game);
+        }
+    }
+
+    #[test]
+    fn strict_test() {
+        let data = "Game 3: 1 blue; 3 red, 4 red";
+        assert_eq!(parse_game(data).unwrap().sets[1].get("red"), 7);
+        let err = parse_game_with(data, ParseMode::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "Game 3, set 2: duplicate colour red");
+
+        let data = "Game 4: 0 blue, 2 red";
+        assert!(parse_game(data).is_ok());
+        let err = parse_game_with(data, ParseMode::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "Game 4, set 1: zero count for blue");
+
+        let data = "Game 5: 1 blue; 2 red!!";
+        assert!(parse_game(data).is_ok());
+        let err = parse_game_with(data, ParseMode::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "Game 5: unexpected trailing input \"!!\"");
+
+        let data = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
+        assert!(parse_game_with(data, ParseMode::Strict).is_ok());
+
+        for (data, msg) in [
+            ("Game 1: 3 red; ; 2 blue", "Game 1, set 2: empty set"),
+            ("Game 1: 3 red; ", "Game 1, set 2: empty set"),
+            ("Game 1: ", "Game 1, set 1: empty set"),
+            ("Game 1:", "Game 1: no sets"),
+        ] {
+            assert!(parse_game(data).is_ok());
+            let err = parse_game_with(data, ParseMode::Strict).unwrap_err();
+            assert_eq!(err.to_string(), msg);
+        }
+    }
+
+    #[test]
+    fn display_test() {
+        let data = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
+        let game = parse_game(data).unwrap();
+        assert_eq!(game.to_string(), data);
+
+        let json = serde_json::to_string(&game.sets[0]).unwrap();
+        assert_eq!(json, r#"{"blue":3,"red":4}"#);
+
+        let game = parse_game("Game 2:").unwrap();
+        assert!(game.sets.is_empty());
+        assert_eq!(game.to_string(), "Game 2:");
+        let game = parse_game("Game 2: ").unwrap();
+        assert_eq!(game.sets, [GameSet::default()]);
+        assert_eq!(game.to_string(), "Game 2: ");
+    }
 
     #[test]
     fn line_test() {
@@ -66,14 +207,34 @@ mod tests {
         let game = parse_game(data).unwrap();
         assert_eq!(game.id, 1);
         let max_set = game.max_set();
-        assert_eq!(max_set.red, 4);
-        assert_eq!(max_set.green, 2);
-        assert_eq!(max_set.blue, 6);
-        assert!(game.check(&GameSet {
-            red: 4,
-            green: 2,
-            blue: 6
-        }));
-        assert_eq!(game.power(), 48);
+        assert_eq!(max_set.get("red"), 4);
+        assert_eq!(max_set.get("green"), 2);
+        assert_eq!(max_set.get("blue"), 6);
+        assert!(game.check(&GameSet::build(4, 2, 6)));
+        assert_eq!(game.power_over(&["red", "green", "blue"]), 48);
+
+        let other = parse_game("Game 2: 3 blue; 1 blue").unwrap();
+        let games = [game, other];
+        assert_eq!(colors(&games), ["blue", "red", "green"]);
+        assert_eq!(games[1].power_over(&colors(&games)), 0);
+        assert_eq!(games[1].power_over(&["blue"]), 3);
+    }
+
+    #[test]
+    fn extra_color_test() {
+        let data = "Game 7: 3 blue, 4 violet; 1 red, 2 violet, 6 blue; 2 green";
+        let game = parse_game(data).unwrap();
+        assert_eq!(game.max_set().get("violet"), 4);
+        assert_eq!(game.power_over(&colors(std::slice::from_ref(&game))), 48);
+        assert_eq!(game.power_over(&["red", "green", "blue"]), 12);
+
+        let limit = GameSet::build(12, 13, 14);
+        assert!(!game.check(&limit));
+        assert!(game.check(&GameSet::from_counts(&[
+            ("red", 12),
+            ("green", 13),
+            ("blue", 14),
+            ("violet", 4),
+        ])));
     }
 }
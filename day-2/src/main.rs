@@ -1,11 +1,24 @@
 pub mod game;
 pub mod game_parser;
+pub mod query;
 
-use game::GameSet;
+use anyhow::{bail, Result};
+use game::{colors, Game, GameSet};
 use game_parser::parse_game;
-use std::fs;
+use query::Query;
+use std::{env, fs};
 
+// With arguments, runs a query instead of the puzzle:
+// `cargo run -- <filter|ids|power> "<expression>" [file]`
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run_query(&args) {
+            println!("{e}");
+        }
+        return;
+    }
+
     let data = fs::read_to_string("data/day-2-input.txt").unwrap();
 
     let answer_one = proc(&data, &GameSet::build(12, 13, 14));
@@ -28,10 +41,35 @@ fn proc_line(line: &str, set_limit: &GameSet) -> u32 {
     }
 }
 
+// Power is taken over every colour in the input, so a game that never shows
+// one of them has power 0.
 fn proc_2(data: &str) -> u32 {
-    data.lines()
-        .map(|line| parse_game(line).unwrap().power())
-        .sum()
+    let games: Vec<Game> = data.lines().map(|l| parse_game(l).unwrap()).collect();
+    let colors = colors(&games);
+    games.iter().map(|g| g.power_over(&colors)).sum()
+}
+
+fn run_query(args: &[String]) -> Result<()> {
+    let (command, expr, path) = match args {
+        [command, expr] => (command, expr, "data/day-2-input.txt"),
+        [command, expr, path] => (command, expr, path.as_str()),
+        _ => bail!("Usage: <filter|ids|power> <expression> [file]"),
+    };
+
+    let query = Query::compile(expr)?;
+    let data = fs::read_to_string(path)?;
+    let games = data.lines().map(parse_game).collect::<Result<Vec<_>>>()?;
+    let colors = colors(&games);
+    let selected = games.iter().filter(|g| query.eval(g, &colors));
+
+    match command.as_str() {
+        "filter" => selected.for_each(|g| println!("{g}")),
+        "ids" => println!("{}", selected.map(|g| g.id).sum::<u32>()),
+        "power" => println!("{}", selected.map(|g| g.power_over(&colors)).sum::<u32>()),
+        _ => bail!("Unknown command {command}"),
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -46,5 +84,9 @@ mod tests {
 
         let res_2 = proc_2(&data);
         assert_eq!(res_2, 2286);
+
+        let data = "Game 1: 3 blue, 4 violet; 1 red, 2 green\nGame 2: 2 red, 5 blue, 1 green\n";
+        // Game 2 has no violet cubes, so its power is 0.
+        assert_eq!(proc_2(data), 24);
     }
 }
@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Result};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{alpha1, digit1, multispace0};
+use nom::combinator::{all_consuming, map, map_res, value};
+use nom::multi::many0;
+use nom::sequence::{delimited, preceded, tuple};
+use nom::IResult;
+
+use crate::game::Game;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Max,
+    Min,
+    Sum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(u32),
+    Cubes(Aggregate, String),
+    Sets,
+    Id,
+    Power,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+// Compiled form of expressions such as
+// `max(red) <= 12 && min(blue) > 0 || sets >= 4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    Cmp(Value, CmpOp, Value),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Value {
+    // A colour missing from a set counts as zero cubes of that colour.
+    // `power` is taken over `colors`.
+    pub fn eval(&self, game: &Game, colors: &[&str]) -> u32 {
+        match self {
+            Value::Number(n) => *n,
+            Value::Cubes(agg, color) => {
+                let counts = game.sets.iter().map(|s| s.get(color));
+                match agg {
+                    Aggregate::Max => counts.max().unwrap_or(0),
+                    Aggregate::Min => counts.min().unwrap_or(0),
+                    Aggregate::Sum => counts.sum(),
+                }
+            }
+            Value::Sets => game.sets.len() as u32,
+            Value::Id => game.id,
+            Value::Power => game.power_over(colors),
+        }
+    }
+}
+
+impl CmpOp {
+    fn apply(&self, a: u32, b: u32) -> bool {
+        match self {
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+        }
+    }
+}
+
+impl Query {
+    pub fn compile(input: &str) -> Result<Self> {
+        let (_, query) = all_consuming(delimited(multispace0, or_expr, multispace0))(input)
+            .map_err(|e| anyhow!("Bad query {input:?}: {e}"))?;
+
+        Ok(query)
+    }
+
+    pub fn eval(&self, game: &Game, colors: &[&str]) -> bool {
+        match self {
+            Query::Cmp(a, op, b) => op.apply(a.eval(game, colors), b.eval(game, colors)),
+            Query::And(a, b) => a.eval(game, colors) && b.eval(game, colors),
+            Query::Or(a, b) => a.eval(game, colors) || b.eval(game, colors),
+            Query::Not(q) => !q.eval(game, colors),
+        }
+    }
+}
+
+fn ws<'a, O>(
+    inner: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn aggregate(input: &str) -> IResult<&str, Aggregate> {
+    alt((
+        value(Aggregate::Max, tag("max")),
+        value(Aggregate::Min, tag("min")),
+        value(Aggregate::Sum, tag("sum")),
+    ))(input)
+}
+
+fn value_expr(input: &str) -> IResult<&str, Value> {
+    alt((
+        map(map_res(digit1, |s: &str| s.parse::<u32>()), Value::Number),
+        map(
+            tuple((aggregate, ws(tag("(")), alpha1, ws(tag(")")))),
+            |(agg, _, color, _)| Value::Cubes(agg, color.to_string()),
+        ),
+        value(Value::Sets, tag("sets")),
+        value(Value::Id, tag("id")),
+        value(Value::Power, tag("power")),
+    ))(input)
+}
+
+fn cmp_op(input: &str) -> IResult<&str, CmpOp> {
+    alt((
+        value(CmpOp::Le, tag("<=")),
+        value(CmpOp::Ge, tag(">=")),
+        value(CmpOp::Eq, tag("==")),
+        value(CmpOp::Ne, tag("!=")),
+        value(CmpOp::Lt, tag("<")),
+        value(CmpOp::Gt, tag(">")),
+    ))(input)
+}
+
+fn cmp_expr(input: &str) -> IResult<&str, Query> {
+    map(
+        tuple((ws(value_expr), cmp_op, ws(value_expr))),
+        |(a, op, b)| Query::Cmp(a, op, b),
+    )(input)
+}
+
+fn unary_expr(input: &str) -> IResult<&str, Query> {
+    alt((
+        map(preceded(ws(tag("!")), unary_expr), |q| {
+            Query::Not(Box::new(q))
+        }),
+        delimited(ws(tag("(")), or_expr, ws(tag(")"))),
+        cmp_expr,
+    ))(input)
+}
+
+fn and_expr(input: &str) -> IResult<&str, Query> {
+    let (input, first) = unary_expr(input)?;
+    let (input, rest) = many0(preceded(tag("&&"), unary_expr))(input)?;
+
+    let query = rest
+        .into_iter()
+        .fold(first, |a, b| Query::And(Box::new(a), Box::new(b)));
+    Ok((input, query))
+}
+
+fn or_expr(input: &str) -> IResult<&str, Query> {
+    let (input, first) = and_expr(input)?;
+    let (input, rest) = many0(preceded(tag("||"), and_expr))(input)?;
+
+    let query = rest
+        .into_iter()
+        .fold(first, |a, b| Query::Or(Box::new(a), Box::new(b)));
+    Ok((input, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::colors;
+    use crate::game_parser::parse_game;
+
+    #[test]
+    fn compile_test() {
+        let query = Query::compile("max(red) <= 12 && min(blue) > 0 || sets >= 4").unwrap();
+        let Query::Or(lhs, rhs) = query else {
+            panic!("expected || at the top");
+        };
+        assert!(matches!(*lhs, Query::And(_, _)));
+        assert_eq!(*rhs, Query::Cmp(Value::Sets, CmpOp::Ge, Value::Number(4)));
+
+        assert!(Query::compile("max(red) <=").is_err());
+        assert!(Query::compile("max(red) <= 3 &&").is_err());
+        assert!(Query::compile("avg(red) > 1").is_err());
+    }
+
+    #[test]
+    fn eval_test() {
+        let game = parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        let rgb = ["red", "green", "blue"];
+        let eval = |q: &str| Query::compile(q).unwrap().eval(&game, &rgb);
+
+        assert!(eval(
+            "max(red) <= 12 && max(green) <= 13 && max(blue) <= 14"
+        ));
+        assert!(eval("min(blue) == 0"));
+        assert!(eval("sum(green) == 4 && sets == 3"));
+        assert!(eval("!(power < 48) && id != 2"));
+        assert!(!eval("max(red) > 4 || (sets >= 4 && id == 1)"));
+
+        let game =
+            parse_game("Game 7: 3 blue, 4 violet; 1 red, 2 violet, 6 blue; 2 green").unwrap();
+        let power = Query::compile("power == 48").unwrap();
+        assert!(power.eval(&game, &colors(std::slice::from_ref(&game))));
+        assert!(!power.eval(&game, &["violet", "blue", "pink"]));
+    }
+}
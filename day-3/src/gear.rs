@@ -0,0 +1,104 @@
+use std::ops::RangeInclusive;
+
+use crate::schematic::Schematic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Aggregation {
+    pub fn apply(&self, numbers: &[u32]) -> u64 {
+        let values = numbers.iter().map(|&n| n as u64);
+        match self {
+            Aggregation::Product => values.product(),
+            Aggregation::Sum => values.sum(),
+            Aggregation::Max => values.max().unwrap_or(0),
+        }
+    }
+}
+
+// Which symbols count as gears, how many numbers they must touch and how
+// those numbers combine into a ratio. The default is the puzzle's rule:
+// a `*` next to exactly two numbers, ratio is their product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub adjacent: RangeInclusive<usize>,
+    pub aggregation: Aggregation,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            adjacent: 2..=2,
+            aggregation: Aggregation::Product,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub x: u32,
+    pub y: u32,
+    pub symbol: char,
+    pub numbers: Vec<u32>,
+    pub ratio: u64,
+}
+
+pub fn find_gear_details(schematic: &Schematic, rule: &GearRule) -> Vec<Gear> {
+    let mut ret = vec![];
+    for (i, s) in schematic.symbols.iter().enumerate() {
+        if !rule.symbols.contains(&s.symbol) {
+            continue;
+        }
+
+        let numbers: Vec<u32> = schematic
+            .numbers_near_symbol(i)
+            .into_iter()
+            .map(|d| schematic.digits[d].value)
+            .collect();
+        if rule.adjacent.contains(&numbers.len()) {
+            ret.push(Gear {
+                x: s.x,
+                y: s.y,
+                symbol: s.symbol,
+                ratio: rule.aggregation.apply(&numbers),
+                numbers,
+            });
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_parser;
+
+    #[test]
+    fn test_rules() {
+        let data = std::fs::read_to_string("data/test.txt").unwrap();
+        let (digits, symbols) = data_parser(&data);
+        let schematic = Schematic::new(digits, symbols);
+
+        let gears = find_gear_details(&schematic, &GearRule::default());
+        assert_eq!(gears.len(), 2);
+        assert_eq!((gears[0].x, gears[0].y), (3, 1));
+        assert_eq!(gears[0].numbers, [467, 35]);
+        assert_eq!(gears[0].ratio, 16345);
+
+        let rule = GearRule {
+            symbols: vec!['*', '+', '#'],
+            adjacent: 1..=3,
+            aggregation: Aggregation::Max,
+        };
+        let gears = find_gear_details(&schematic, &rule);
+        let ratios: Vec<u64> = gears.iter().map(|g| g.ratio).collect();
+        assert_eq!(ratios, [467, 633, 617, 592, 755]);
+        assert_eq!(gears[2].symbol, '*');
+    }
+}
@@ -1,7 +1,28 @@
-use std::fs;
+pub mod gear;
+pub mod render;
+pub mod schematic;
+
+use gear::{find_gear_details, GearRule};
+use render::{render, Format};
+use schematic::Schematic;
+use std::{env, fs};
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
+
+    // `--ansi` or `--html` prints the annotated schematic instead.
+    let format = match env::args().nth(1).as_deref() {
+        Some("--ansi") => Some(Format::Ansi),
+        Some("--html") => Some(Format::Html),
+        _ => None,
+    };
+    if let Some(format) = format {
+        let (digits, symbols) = data_parser(&data);
+        let schematic = Schematic::new(digits, symbols);
+        let gears = find_gear_details(&schematic, &GearRule::default());
+        print!("{}", render(&data, &schematic, &gears, format));
+        return;
+    }
     let (digits, symbols) = data_parser(&data);
     let res = find_items(digits, symbols);
     println!("Day 3 part one: {res}");
@@ -19,7 +40,7 @@ enum ParserState {
 }
 
 #[derive(Debug)]
-struct Digits {
+pub struct Digits {
     value: u32,
     x: u32,
     x_end: u32,
@@ -27,10 +48,10 @@ struct Digits {
 }
 
 #[derive(Debug)]
-struct Symbols {
+pub struct Symbols {
     x: u32,
     y: u32,
-    gear: bool,
+    symbol: char,
 }
 
 fn data_parser(input: &str) -> (Vec<Digits>, Vec<Symbols>) {
@@ -73,8 +94,7 @@ fn data_parser(input: &str) -> (Vec<Digits>, Vec<Symbols>) {
                 }
             }
             ParserState::Symbol => {
-                let gear = *c == '*';
-                symbols.push(Symbols { x, y, gear });
+                symbols.push(Symbols { x, y, symbol: *c });
                 state = ParserState::Start;
             }
         }
@@ -93,37 +113,19 @@ fn data_parser(input: &str) -> (Vec<Digits>, Vec<Symbols>) {
 }
 
 fn find_items(digits: Vec<Digits>, symbols: Vec<Symbols>) -> u32 {
-    let mut ret = 0;
-    for d in digits {
-        for s in &symbols {
-            if d.y + 1 >= s.y && d.y <= s.y + 1 && s.x + 1 >= d.x && s.x <= d.x_end + 1 {
-                ret += d.value;
-                break;
-            }
-        }
-    }
-    ret
+    let schematic = Schematic::new(digits, symbols);
+    (0..schematic.digits.len())
+        .filter(|&d| schematic.is_part(d))
+        .map(|d| schematic.digits[d].value)
+        .sum()
 }
 
-fn find_gears(digits: Vec<Digits>, symbols: Vec<Symbols>) -> u32 {
-    let mut ret = 0;
-    for s in symbols {
-        let mut adjacent = 0;
-        let mut gear_ratio = 1;
-        if !s.gear {
-            continue;
-        }
-        for d in &digits {
-            if d.y + 1 >= s.y && d.y <= s.y + 1 && s.x + 1 >= d.x && s.x <= d.x_end + 1 {
-                adjacent += 1;
-                gear_ratio *= d.value;
-            }
-        }
-        if adjacent == 2 {
-            ret += gear_ratio;
-        }
-    }
-    ret
+fn find_gears(digits: Vec<Digits>, symbols: Vec<Symbols>) -> u64 {
+    let schematic = Schematic::new(digits, symbols);
+    find_gear_details(&schematic, &GearRule::default())
+        .iter()
+        .map(|g| g.ratio)
+        .sum()
 }
 
 #[cfg(test)]
@@ -0,0 +1,155 @@
+use crate::gear::Gear;
+use crate::schematic::Schematic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ansi,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Plain,
+    Part,
+    NonPart,
+    Gear,
+}
+
+impl Class {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Class::Plain => "",
+            Class::Part => "\x1b[32m",
+            Class::NonPart => "\x1b[31m",
+            Class::Gear => "\x1b[1;33m",
+        }
+    }
+
+    fn html(&self) -> &'static str {
+        match self {
+            Class::Plain => "",
+            Class::Part => "part",
+            Class::NonPart => "non-part",
+            Class::Gear => "gear",
+        }
+    }
+}
+
+const HTML_STYLE: &str = "<style>\
+.part { color: green; } \
+.non-part { color: red; } \
+.gear { color: orange; font-weight: bold; }\
+</style>\n";
+
+fn classify(input: &str, schematic: &Schematic, gears: &[Gear]) -> Vec<Vec<Class>> {
+    let mut classes: Vec<Vec<Class>> = input
+        .lines()
+        .map(|l| vec![Class::Plain; l.chars().count()])
+        .collect();
+
+    for (i, d) in schematic.digits.iter().enumerate() {
+        let class = if schematic.is_part(i) {
+            Class::Part
+        } else {
+            Class::NonPart
+        };
+        for x in d.x..=d.x_end {
+            classes[d.y as usize][x as usize] = class;
+        }
+    }
+    for g in gears {
+        classes[g.y as usize][g.x as usize] = Class::Gear;
+    }
+
+    classes
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        _ => c.to_string(),
+    }
+}
+
+// Reproduces `input` with counted part numbers, numbers that aren't parts and
+// the given gears highlighted. Runs of the same class share one colour code.
+pub fn render(input: &str, schematic: &Schematic, gears: &[Gear], format: Format) -> String {
+    let classes = classify(input, schematic, gears);
+    let mut ret = String::new();
+
+    if format == Format::Html {
+        ret.push_str(HTML_STYLE);
+        ret.push_str("<pre class=\"schematic\">");
+    }
+
+    for (line, line_classes) in input.lines().zip(&classes) {
+        let mut current = Class::Plain;
+        for (c, &class) in line.chars().zip(line_classes) {
+            if class != current {
+                match format {
+                    Format::Ansi => {
+                        if current != Class::Plain {
+                            ret.push_str("\x1b[0m");
+                        }
+                        ret.push_str(class.ansi());
+                    }
+                    Format::Html => {
+                        if current != Class::Plain {
+                            ret.push_str("</span>");
+                        }
+                        if class != Class::Plain {
+                            ret.push_str(&format!("<span class=\"{}\">", class.html()));
+                        }
+                    }
+                }
+                current = class;
+            }
+            match format {
+                Format::Ansi => ret.push(c),
+                Format::Html => ret.push_str(&escape(c)),
+            }
+        }
+        if current != Class::Plain {
+            ret.push_str(match format {
+                Format::Ansi => "\x1b[0m",
+                Format::Html => "</span>",
+            });
+        }
+        ret.push('\n');
+    }
+
+    if format == Format::Html {
+        ret.push_str("</pre>\n");
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_parser;
+    use crate::gear::{find_gear_details, GearRule};
+
+    #[test]
+    fn test_render() {
+        let data = "467..114..\n...*......\n..35..633.\n";
+        let (digits, symbols) = data_parser(data);
+        let schematic = Schematic::new(digits, symbols);
+        let gears = find_gear_details(&schematic, &GearRule::default());
+
+        let ansi = render(data, &schematic, &gears, Format::Ansi);
+        let lines: Vec<&str> = ansi.lines().collect();
+        assert_eq!(lines[0], "\x1b[32m467\x1b[0m..\x1b[31m114\x1b[0m..");
+        assert_eq!(lines[1], "...\x1b[1;33m*\x1b[0m......");
+        assert_eq!(lines[2], "..\x1b[32m35\x1b[0m..\x1b[31m633\x1b[0m.");
+
+        let html = render(data, &schematic, &gears, Format::Html);
+        assert!(
+            html.contains("<span class=\"part\">467</span>..<span class=\"non-part\">114</span>")
+        );
+        assert!(html.contains("...<span class=\"gear\">*</span>......"));
+    }
+}
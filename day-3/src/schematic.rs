@@ -0,0 +1,128 @@
+use crate::{Digits, Symbols};
+
+const EMPTY: u32 = u32::MAX;
+
+// Numbers and symbols together with a grid that maps each cell to the number
+// or symbol covering it, so neighbours are found by looking at the cells
+// around an item instead of scanning every other item.
+#[derive(Debug)]
+pub struct Schematic {
+    pub digits: Vec<Digits>,
+    pub symbols: Vec<Symbols>,
+    width: u32,
+    height: u32,
+    digit_grid: Vec<u32>,
+    symbol_grid: Vec<u32>,
+}
+
+impl Schematic {
+    pub fn new(digits: Vec<Digits>, symbols: Vec<Symbols>) -> Self {
+        let width = digits
+            .iter()
+            .map(|d| d.x_end + 1)
+            .chain(symbols.iter().map(|s| s.x + 1))
+            .max()
+            .unwrap_or(0);
+        let height = digits
+            .iter()
+            .map(|d| d.y + 1)
+            .chain(symbols.iter().map(|s| s.y + 1))
+            .max()
+            .unwrap_or(0);
+
+        let size = (width * height) as usize;
+        let mut digit_grid = vec![EMPTY; size];
+        let mut symbol_grid = vec![EMPTY; size];
+
+        for (i, d) in digits.iter().enumerate() {
+            for x in d.x..=d.x_end {
+                digit_grid[(d.y * width + x) as usize] = i as u32;
+            }
+        }
+        for (i, s) in symbols.iter().enumerate() {
+            symbol_grid[(s.y * width + s.x) as usize] = i as u32;
+        }
+
+        Schematic {
+            digits,
+            symbols,
+            width,
+            height,
+            digit_grid,
+            symbol_grid,
+        }
+    }
+
+    fn lookup(&self, grid: &[u32], x: i64, y: i64) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        match grid[(y as u32 * self.width + x as u32) as usize] {
+            EMPTY => None,
+            i => Some(i as usize),
+        }
+    }
+
+    // Indices of the numbers touching symbol `idx`, each listed once.
+    pub fn numbers_near_symbol(&self, idx: usize) -> Vec<usize> {
+        let s = &self.symbols[idx];
+        let (x, y) = (s.x as i64, s.y as i64);
+        let mut ret = vec![];
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if let Some(d) = self.lookup(&self.digit_grid, x + dx, y + dy) {
+                    if !ret.contains(&d) {
+                        ret.push(d);
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    // Indices of the symbols touching number `idx`.
+    pub fn symbols_near_number(&self, idx: usize) -> Vec<usize> {
+        let d = &self.digits[idx];
+        let mut ret = vec![];
+
+        for y in d.y as i64 - 1..=d.y as i64 + 1 {
+            for x in d.x as i64 - 1..=d.x_end as i64 + 1 {
+                if let Some(s) = self.lookup(&self.symbol_grid, x, y) {
+                    ret.push(s);
+                }
+            }
+        }
+
+        ret
+    }
+
+    pub fn is_part(&self, idx: usize) -> bool {
+        !self.symbols_near_number(idx).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_parser;
+
+    #[test]
+    fn test_adjacency() {
+        let (digits, symbols) = data_parser("467..114..\n...*......\n..35..633.\n");
+        let schematic = Schematic::new(digits, symbols);
+
+        let mut near: Vec<u32> = schematic
+            .numbers_near_symbol(0)
+            .into_iter()
+            .map(|d| schematic.digits[d].value)
+            .collect();
+        near.sort();
+        assert_eq!(near, [35, 467]);
+
+        assert_eq!(schematic.symbols_near_number(0), [0]);
+        assert!(!schematic.is_part(1));
+        assert!(!schematic.is_part(3));
+    }
+}
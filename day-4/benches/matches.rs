@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Shared with the binary; its unit tests are compiled out of the bench.
+#[allow(unused_imports)]
+#[path = "../src/number_set.rs"]
+mod number_set;
+
+use number_set::{count_matches, hash_matches};
+
+const CARDS: usize = 1_000_000;
+
+// Deterministic cards shaped like the puzzle input: 10 winning numbers and
+// 25 numbers you have, all in 1..100.
+fn generate_cards() -> Vec<(Vec<u32>, Vec<u32>)> {
+    let mut state: u64 = 0x2023_1204;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        ((state >> 33) % 99 + 1) as u32
+    };
+
+    (0..CARDS)
+        .map(|_| {
+            let winning = (0..10).map(|_| next()).collect();
+            let have = (0..25).map(|_| next()).collect();
+            (have, winning)
+        })
+        .collect()
+}
+
+fn bench_matches(c: &mut Criterion) {
+    let cards = generate_cards();
+    let mut group = c.benchmark_group("million_cards");
+    group.sample_size(10);
+
+    group.bench_function("bitset", |b| {
+        b.iter(|| {
+            cards
+                .iter()
+                .map(|(have, winning)| count_matches(black_box(have), black_box(winning)))
+                .sum::<u32>()
+        })
+    });
+
+    group.bench_function("hash_set", |b| {
+        b.iter(|| {
+            cards
+                .iter()
+                .map(|(have, winning)| hash_matches(black_box(have), black_box(winning)))
+                .sum::<u32>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_matches);
+criterion_main!(benches);
@@ -1,19 +1,26 @@
+pub mod number_set;
+pub mod scoring;
+
 use nom::bytes::complete::tag;
 use nom::character::complete::{digit1, space0, space1};
 use nom::combinator::map_res;
 use nom::multi::separated_list1;
 use nom::IResult;
-use std::collections::HashSet;
+use number_set::count_matches;
+use scoring::{CascadeRule, DoublingRule, ScoringRule};
 use std::fs;
-use std::iter::FromIterator;
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
-    let total = proc_one(&data);
-    println!("Day 4 part one: {total}");
+    match proc_one(&data) {
+        Some(total) => println!("Day 4 part one: {total}"),
+        None => eprintln!("Day 4 part one: the score doesn't fit in a u64"),
+    }
 
-    let total = proc_two(&data);
-    println!("Day 4 part two: {total}");
+    match proc_two(&data) {
+        Some(total) => println!("Day 4 part two: {total}"),
+        None => eprintln!("Day 4 part two: the score doesn't fit in a u64"),
+    }
 }
 
 fn digit1_padded(input: &str) -> IResult<&str, u32> {
@@ -37,41 +44,66 @@ fn parse_line(input: &str) -> (u32, Vec<u32>, Vec<u32>) {
 }
 
 fn get_matches(numbers_have: Vec<u32>, numbers_winning: Vec<u32>) -> u32 {
-    let have: HashSet<u32> = HashSet::from_iter(numbers_have);
-    let winning: HashSet<u32> = HashSet::from_iter(numbers_winning);
-    have.intersection(&winning).count() as u32
+    count_matches(&numbers_have, &numbers_winning)
 }
 
-fn calc_score(count: u32) -> u32 {
+fn calc_score(count: u32) -> u64 {
     match count {
         0 => 0,
-        x => 2_u32.pow(x - 1),
+        x => 2_u64.pow(x - 1),
     }
 }
 
-fn proc_one(data: &str) -> u32 {
+fn card_matches(data: &str) -> Vec<u32> {
     data.lines()
         .map(parse_line)
         .map(|(_, have, winning)| get_matches(have, winning))
-        .map(calc_score)
-        .sum()
+        .collect()
 }
 
-fn proc_two(data: &str) -> u32 {
-    let mut cards = data
-        .lines()
-        .map(parse_line)
-        .map(|(_, have, winning)| (1, get_matches(have, winning)))
-        .collect::<Vec<(u32, u32)>>();
+// None when the score doesn't fit in a u64.
+fn proc(data: &str, rule: &dyn ScoringRule) -> Option<u64> {
+    rule.score(&card_matches(data))
+}
 
-    for i in 0..cards.len() {
-        let (count, winning) = cards[i];
-        for q in 1..=winning {
-            cards[i + q as usize].0 += count;
+fn proc_one(data: &str) -> Option<u64> {
+    proc(data, &DoublingRule)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Cascade {
+    copies: Vec<u64>,
+    total: u64,
+}
+
+// Each card wins one copy of each of the next `matches` cards, for every copy
+// of itself. Wins past the last card are dropped. Returns None if a count
+// doesn't fit in a u64.
+fn cascade(matches: &[u32]) -> Option<Cascade> {
+    cascade_with(matches, Some)
+}
+
+// Like `cascade`, but `won` turns the number of copies of a card into the
+// number of copies it wins of each following card.
+fn cascade_with(matches: &[u32], won: impl Fn(u64) -> Option<u64>) -> Option<Cascade> {
+    let mut copies = vec![1_u64; matches.len()];
+
+    for i in 0..matches.len() {
+        let count = won(copies[i])?;
+        let last = (i + matches[i] as usize).min(matches.len() - 1);
+        for c in &mut copies[i + 1..=last] {
+            *c = c.checked_add(count)?;
         }
     }
 
-    cards.iter().map(|card| card.0).sum()
+    let total = copies
+        .iter()
+        .try_fold(0_u64, |acc, &c| acc.checked_add(c))?;
+    Some(Cascade { copies, total })
+}
+
+fn proc_two(data: &str) -> Option<u64> {
+    proc(data, &CascadeRule)
 }
 
 #[cfg(test)]
@@ -104,7 +136,7 @@ mod tests {
     fn test_file() {
         let data = fs::read_to_string("data/test.txt").unwrap();
         let total = proc_one(&data);
-        assert_eq!(total, 13);
+        assert_eq!(total, Some(13));
     }
 
     #[test]
@@ -119,6 +151,30 @@ mod tests {
     fn test_part_two() {
         let data = fs::read_to_string("data/test.txt").unwrap();
         let total = proc_two(&data);
-        assert_eq!(total, 30);
+        assert_eq!(total, Some(30));
+
+        let numbers = (1..=70)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let data = (1..=70)
+            .map(|i| format!("Card {i}: {numbers} | {numbers}\n"))
+            .collect::<String>();
+        assert_eq!(proc_two(&data), None);
+        assert_eq!(proc_one(&data), None);
+    }
+
+    #[test]
+    fn test_cascade() {
+        let res = cascade(&[4, 2, 2, 1, 0, 0]).unwrap();
+        assert_eq!(res.copies, [1, 2, 4, 8, 14, 1]);
+        assert_eq!(res.total, 30);
+
+        let res = cascade(&[1, 5, 3]).unwrap();
+        assert_eq!(res.copies, [1, 2, 3]);
+        assert_eq!(res.total, 6);
+
+        assert!(cascade(&[]).unwrap().copies.is_empty());
+        assert!(cascade(&[70; 70]).is_none());
     }
 }
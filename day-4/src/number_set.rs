@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+const WIDTH: u32 = 128;
+
+// Card numbers below 128 packed one bit per value, so the matches between two
+// cards are a couple of ANDs and popcounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberSet([u64; 2]);
+
+impl NumberSet {
+    // None if any value is too large for the bitset.
+    pub fn from_slice(values: &[u32]) -> Option<Self> {
+        let mut set = NumberSet::default();
+        for &v in values {
+            if v >= WIDTH {
+                return None;
+            }
+            set.0[(v / 64) as usize] |= 1 << (v % 64);
+        }
+        Some(set)
+    }
+
+    pub fn count_common(&self, other: &NumberSet) -> u32 {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a & b).count_ones())
+            .sum()
+    }
+}
+
+pub fn hash_matches(have: &[u32], winning: &[u32]) -> u32 {
+    let have: HashSet<&u32> = have.iter().collect();
+    let winning: HashSet<&u32> = winning.iter().collect();
+    have.intersection(&winning).count() as u32
+}
+
+pub fn count_matches(have: &[u32], winning: &[u32]) -> u32 {
+    match (NumberSet::from_slice(have), NumberSet::from_slice(winning)) {
+        (Some(have), Some(winning)) => have.count_common(&winning),
+        _ => hash_matches(have, winning),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_matches() {
+        let have = [83, 86, 6, 31, 17, 9, 48, 53];
+        let winning = [41, 48, 83, 86, 17];
+        assert_eq!(count_matches(&have, &winning), 4);
+        assert_eq!(hash_matches(&have, &winning), 4);
+
+        assert_eq!(count_matches(&[0, 64, 127, 127], &[127, 64, 1]), 2);
+        assert_eq!(count_matches(&[5, 1000], &[1000, 5, 6]), 2);
+        assert!(NumberSet::from_slice(&[128]).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{calc_score, cascade, cascade_with};
+
+// Turns the number of matches on each card, in card order, into a total
+// score. None means the score doesn't fit in a u64.
+pub trait ScoringRule {
+    fn score(&self, matches: &[u32]) -> Option<u64>;
+}
+
+// Part one: 1 point for the first match, doubled for every further one.
+pub struct DoublingRule;
+
+// Part two: every card wins copies of the cards below it, the score is the
+// number of cards you end up with.
+pub struct CascadeRule;
+
+pub struct LinearRule {
+    pub points_per_match: u64,
+}
+
+// Like `CascadeRule`, but a card only wins `keep_percent` percent (rounded
+// up) of its copy count for each following card.
+pub struct CascadeDecayRule {
+    pub keep_percent: u64,
+}
+
+impl ScoringRule for DoublingRule {
+    fn score(&self, matches: &[u32]) -> Option<u64> {
+        matches.iter().try_fold(0_u64, |acc, &m| match m {
+            0..=64 => acc.checked_add(calc_score(m)),
+            _ => None,
+        })
+    }
+}
+
+impl ScoringRule for CascadeRule {
+    fn score(&self, matches: &[u32]) -> Option<u64> {
+        cascade(matches).map(|c| c.total)
+    }
+}
+
+impl ScoringRule for LinearRule {
+    fn score(&self, matches: &[u32]) -> Option<u64> {
+        matches.iter().try_fold(0_u64, |acc, &m| {
+            acc.checked_add((m as u64).checked_mul(self.points_per_match)?)
+        })
+    }
+}
+
+impl ScoringRule for CascadeDecayRule {
+    fn score(&self, matches: &[u32]) -> Option<u64> {
+        cascade_with(matches, |count| {
+            count
+                .checked_mul(self.keep_percent)
+                .map(|c| c.div_ceil(100))
+        })
+        .map(|c| c.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATCHES: [u32; 6] = [4, 2, 2, 1, 0, 0];
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(DoublingRule.score(&MATCHES), Some(13));
+        assert_eq!(CascadeRule.score(&MATCHES), Some(30));
+        assert_eq!(
+            LinearRule {
+                points_per_match: 3
+            }
+            .score(&MATCHES),
+            Some(27)
+        );
+
+        let full = CascadeDecayRule { keep_percent: 100 };
+        assert_eq!(full.score(&MATCHES), Some(30));
+        // Card 1 (1 copy) wins 1 each of cards 2..5, card 2 (2 copies) 1 each
+        // of 3..4, card 3 (3 copies) 2 each of 4..5, card 4 (5 copies) 3 of
+        // card 5.
+        let half = CascadeDecayRule { keep_percent: 50 };
+        assert_eq!(half.score(&MATCHES), Some(1 + 2 + 3 + 5 + 7 + 1));
+
+        assert_eq!(DoublingRule.score(&[64]), Some(1 << 63));
+        assert_eq!(DoublingRule.score(&[65]), None);
+    }
+}
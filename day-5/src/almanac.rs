@@ -0,0 +1,207 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::num::Value;
+use crate::piecewise::Piecewise;
+use crate::{map_parser, MapBlock, NamedBlock, SeedRange};
+
+// Seeds and map blocks along with the categories each block converts between.
+// Blocks must form a single chain: every block starts where the previous one
+// ended. No rule may run past `T::MAX` on either its source or destination
+// side.
+//
+// Conversions go through the blocks composed into one `Piecewise` map, built
+// once for the whole chain.
+#[derive(Debug)]
+pub struct Almanac<T: Value = u64> {
+    pub seeds: Vec<T>,
+    pub categories: Vec<String>,
+    pub blocks: Vec<MapBlock<T>>,
+    pub composed: Piecewise<T>,
+}
+
+impl<T: Value> Almanac<T> {
+    pub fn new(seeds: Vec<T>, named_blocks: Vec<NamedBlock<T>>) -> Result<Self> {
+        let mut categories: Vec<String> = vec![];
+        let mut blocks = vec![];
+
+        for (source, destination, rules) in named_blocks {
+            match categories.last() {
+                None => categories.push(source.to_string()),
+                Some(last) if last == source => {}
+                Some(last) => {
+                    bail!("Chain broken: {last} is followed by {source}-to-{destination}")
+                }
+            }
+            if categories.iter().any(|c| c == destination) {
+                bail!("Category {destination} appears twice in the chain");
+            }
+            for &(dst, src, len) in &rules {
+                if len > T::ZERO && (src.range_end(len).is_none() || dst.range_end(len).is_none()) {
+                    bail!(
+                        "Rule {dst} {src} {len} in {source}-to-{destination} runs past {}",
+                        T::MAX
+                    );
+                }
+            }
+            categories.push(destination.to_string());
+            blocks.push(rules);
+        }
+
+        let composed = Piecewise::compose(&blocks);
+
+        Ok(Almanac {
+            seeds,
+            categories,
+            blocks,
+            composed,
+        })
+    }
+
+    pub fn parse(data: &str) -> Result<Self> {
+        let (input, (seeds, blocks)) = map_parser(data).map_err(|e| e.to_owned())?;
+        if !input.is_empty() {
+            bail!(
+                "Unexpected input after the last map: {:?}",
+                input.lines().next()
+            );
+        }
+
+        Self::new(seeds, blocks)
+    }
+
+    // Seeds read as start and length pairs. Empty ranges are dropped.
+    pub fn seed_ranges(&self) -> Result<Vec<SeedRange<T>>> {
+        if !self.seeds.len().is_multiple_of(2) {
+            bail!("Seed ranges need a length for every start");
+        }
+
+        let mut ret = vec![];
+        for c in self.seeds.chunks(2) {
+            if c[1] == T::ZERO {
+                continue;
+            }
+            match c[0].range_end(c[1]) {
+                Some(end) => ret.push((c[0], end)),
+                None => bail!("Seed range {} {} runs past {}", c[0], c[1], T::MAX),
+            }
+        }
+        Ok(ret)
+    }
+
+    fn category(&self, name: &str) -> Result<usize> {
+        self.categories
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| anyhow!("Unknown category {name}"))
+    }
+
+    // Indices of the blocks converting `from` into `to`.
+    fn span(&self, from: &str, to: &str) -> Result<(usize, usize)> {
+        let (start, end) = (self.category(from)?, self.category(to)?);
+        if start > end {
+            bail!("{from} comes after {to} in the almanac");
+        }
+
+        Ok((start, end))
+    }
+
+    // Blocks converting `from` into `to`, in the order they have to be applied.
+    pub fn chain(&self, from: &str, to: &str) -> Result<&[MapBlock<T>]> {
+        let (start, end) = self.span(from, to)?;
+        Ok(&self.blocks[start..end])
+    }
+
+    // The map converting `from` into `to`. Only part of the chain has to be
+    // composed again.
+    pub fn map(&self, from: &str, to: &str) -> Result<Cow<'_, Piecewise<T>>> {
+        let (start, end) = self.span(from, to)?;
+        if (start, end) == (0, self.blocks.len()) {
+            return Ok(Cow::Borrowed(&self.composed));
+        }
+
+        Ok(Cow::Owned(Piecewise::compose(&self.blocks[start..end])))
+    }
+
+    pub fn convert(&self, value: T, from: &str, to: &str) -> Result<T> {
+        Ok(self.map(from, to)?.apply(value))
+    }
+
+    pub fn convert_range(
+        &self,
+        range: SeedRange<T>,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<SeedRange<T>>> {
+        Ok(self.map(from, to)?.apply_range(range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert_seed_range;
+    use std::fs;
+
+    #[test]
+    fn test_convert() {
+        let data = fs::read_to_string("data/test.txt").unwrap();
+        let almanac: Almanac = Almanac::parse(&data).unwrap();
+        assert_eq!(almanac.categories.len(), 8);
+
+        assert_eq!(almanac.convert(79, "seed", "soil").unwrap(), 81);
+        assert_eq!(almanac.convert(81, "soil", "humidity").unwrap(), 78);
+        assert_eq!(almanac.convert(79, "seed", "location").unwrap(), 82);
+        assert_eq!(almanac.convert(79, "seed", "seed").unwrap(), 79);
+        assert_eq!(
+            almanac.convert_range((79, 92), "soil", "humidity").unwrap(),
+            convert_seed_range((79, 92), almanac.chain("soil", "humidity").unwrap())
+        );
+
+        assert!(almanac.convert(79, "location", "seed").is_err());
+        assert!(almanac.convert(79, "seed", "water2").is_err());
+    }
+
+    #[test]
+    fn test_broken_chain() {
+        let data = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3\n";
+        let err = Almanac::<u64>::parse(data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Chain broken: soil is followed by water-to-light"
+        );
+
+        let data = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-seed map:\n1 2 3\n";
+        assert!(Almanac::<u64>::parse(data).is_err());
+    }
+
+    #[test]
+    fn test_extremes() {
+        let data = "seeds: 4294967290 6\n\nseed-to-soil map:\n0 4294967291 5\n";
+        let almanac = Almanac::<u32>::parse(data).unwrap();
+        assert_eq!(almanac.seed_ranges().unwrap(), [(u32::MAX - 5, u32::MAX)]);
+        assert_eq!(almanac.convert(u32::MAX, "seed", "soil").unwrap(), 4);
+        let res = almanac
+            .convert_range((u32::MAX - 5, u32::MAX), "seed", "soil")
+            .unwrap();
+        assert_eq!(res, [(0, 4), (u32::MAX - 5, u32::MAX - 5)]);
+
+        // One past u32::MAX still parses as u64.
+        let data = "seeds: 4294967296 1\n\nseed-to-soil map:\n0 4294967296 1\n";
+        assert!(Almanac::<u32>::parse(data).is_err());
+        let almanac: Almanac = Almanac::parse(data).unwrap();
+        assert_eq!(almanac.convert(1 << 32, "seed", "soil").unwrap(), 0);
+
+        let data = "seeds: 1 1\n\nseed-to-soil map:\n4294967295 0 2\n";
+        let err = Almanac::<u32>::parse(data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Rule 4294967295 0 2 in seed-to-soil runs past 4294967295"
+        );
+
+        let data = "seeds: 18446744073709551615 2\n\nseed-to-soil map:\n0 0 1\n";
+        let almanac: Almanac = Almanac::parse(data).unwrap();
+        assert!(almanac.seed_ranges().is_err());
+    }
+}
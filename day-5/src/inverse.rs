@@ -0,0 +1,120 @@
+use interval_set::IntervalSet;
+
+use crate::num::Value;
+use crate::piecewise::Piecewise;
+use crate::{MapBlock, SeedRange};
+
+// Blocks in reverse order, for mapping destination values back to the source
+// values that produce them. Values no rule maps pass through unchanged, so a
+// destination value can have a pre-image in a rule's source range, in the
+// identity passthrough, in both, or nowhere.
+#[derive(Debug, Clone)]
+pub struct InverseAlmanac<T = u64> {
+    blocks: Vec<Piecewise<T>>,
+}
+
+impl<T: Value> InverseAlmanac<T> {
+    pub fn new(blocks: &[MapBlock<T>]) -> Self {
+        InverseAlmanac {
+            blocks: blocks.iter().rev().map(Piecewise::from_block).collect(),
+        }
+    }
+
+    pub fn preimage(&self, range: SeedRange<T>) -> Vec<SeedRange<T>> {
+        self.preimage_set(range).ranges().to_vec()
+    }
+
+    fn preimage_set(&self, range: SeedRange<T>) -> IntervalSet<T> {
+        self.blocks
+            .iter()
+            .fold(IntervalSet::from_range(range.0, range.1), |set, block| {
+                set.ranges()
+                    .iter()
+                    .flat_map(|&r| block.preimage(r))
+                    .collect()
+            })
+    }
+
+    // Whether any value in `seeds` maps into `range`.
+    pub fn reaches(&self, range: SeedRange<T>, seeds: &[SeedRange<T>]) -> bool {
+        let seeds = IntervalSet::from_ranges(seeds.iter().copied());
+        !self.preimage_set(range).intersection(&seeds).is_empty()
+    }
+
+    // Lowest location reachable from `seeds`. The pre-image of 0..=n only
+    // grows with n, so the answer can be found by binary search.
+    pub fn lowest_location(&self, seeds: &[SeedRange<T>]) -> Option<T> {
+        if !self.reaches((T::ZERO, T::MAX), seeds) {
+            return None;
+        }
+
+        let (mut lo, mut hi) = (T::ZERO, T::MAX);
+        while lo < hi {
+            let mid = T::from_i128((lo.to_i128() + hi.to_i128()) / 2)?;
+            if self.reaches((T::ZERO, mid), seeds) {
+                hi = mid;
+            } else {
+                lo = mid + T::ONE;
+            }
+        }
+        Some(lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::almanac::Almanac;
+    use crate::apply_transform;
+    use std::fs;
+
+    #[test]
+    fn test_preimage() {
+        let inverse = InverseAlmanac::<u64>::new(&[vec![(50, 98, 2), (52, 50, 48)]]);
+        assert_eq!(inverse.preimage((81, 81)), [(79, 79)]);
+        // 50 and 51 come from 98 and 99 only: the passthrough for 50..=51
+        // is shadowed by the second rule.
+        assert_eq!(inverse.preimage((50, 51)), [(98, 99)]);
+        // 99 is remapped itself, so only 97 lands on it; 100 passes through.
+        assert_eq!(inverse.preimage((99, 100)), [(97, 97), (100, 100)]);
+    }
+
+    #[test]
+    fn test_lowest_location() {
+        let data = fs::read_to_string("data/test.txt").unwrap();
+        let almanac: Almanac = Almanac::parse(&data).unwrap();
+        let inverse = InverseAlmanac::new(&almanac.blocks);
+
+        for loc in [0, 35, 46, 82, 100] {
+            for seed in inverse.preimage((loc, loc)) {
+                assert_eq!(apply_transform(seed.0, &almanac.blocks), loc);
+            }
+        }
+
+        let seeds = almanac.seed_ranges().unwrap();
+        assert_eq!(inverse.lowest_location(&seeds), Some(46));
+        assert_eq!(inverse.lowest_location(&[(79, 79)]), Some(82));
+    }
+
+    #[test]
+    fn test_extremes() {
+        let blocks = [vec![(0, u64::MAX - 4, 5)], vec![(u64::MAX, 0, 1)]];
+        let inverse = InverseAlmanac::new(&blocks);
+        assert_eq!(
+            inverse.preimage((u64::MAX, u64::MAX)),
+            [(0, 0), (u64::MAX - 4, u64::MAX - 4)]
+        );
+        assert_eq!(
+            inverse.lowest_location(&[(u64::MAX - 4, u64::MAX)]),
+            Some(1)
+        );
+        assert_eq!(
+            inverse.lowest_location(&[(u64::MAX - 5, u64::MAX - 5)]),
+            Some(u64::MAX - 5)
+        );
+
+        let inverse = InverseAlmanac::new(&[vec![(u32::MAX - 1, 0, 2)]]);
+        assert_eq!(inverse.lowest_location(&[(0, 0)]), Some(u32::MAX - 1));
+        assert_eq!(inverse.lowest_location(&[(0, u32::MAX)]), Some(2));
+    }
+}
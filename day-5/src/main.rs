@@ -1,55 +1,82 @@
-use std::fs;
+pub mod almanac;
+pub mod inverse;
+pub mod num;
+pub mod piecewise;
 
+use std::{env, fs};
+
+use almanac::Almanac;
+#[cfg(test)]
+use interval_set::IntervalSet;
+use inverse::InverseAlmanac;
 use nom::{
     bytes::complete::tag,
-    character::complete::{digit1, newline, not_line_ending, space1},
+    character::complete::{alpha1, digit1, newline, space1},
     combinator::map_res,
     multi::{many0, many1, separated_list1},
     IResult,
 };
+use num::Value;
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
+
+    // `--print-map` shows the seed-to-location map with every block composed.
+    if env::args().nth(1).as_deref() == Some("--print-map") {
+        let almanac: Almanac = Almanac::parse(&data).unwrap();
+        print!("{}", almanac.composed);
+        return;
+    }
+
     let part_one = proc_1(&data);
     println!("Day 5 part one: {part_one}");
 
-    let part_two = proc_2(&data);
+    // `--inverse` searches backwards from the locations for part two.
+    let part_two = if env::args().any(|a| a == "--inverse") {
+        proc_2_inverse(&data)
+    } else {
+        proc_2(&data)
+    };
     println!("Day 5 part two: {part_two}");
 }
 
-type TableItem = (u32, u32, u32);
-type MapBlock = Vec<TableItem>;
-type SeedRange = (u32, u32);
+type TableItem<T = u64> = (T, T, T);
+type MapBlock<T = u64> = Vec<TableItem<T>>;
+type SeedRange<T = u64> = (T, T);
+type NamedBlock<'a, T = u64> = (&'a str, &'a str, MapBlock<T>);
 
-fn digit1_u32(input: &str) -> IResult<&str, u32> {
-    map_res(digit1, |s: &str| s.parse::<u32>())(input)
+fn digit1_num<T: Value>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, |s: &str| s.parse::<T>())(input)
 }
 
-fn seeds_parser(input: &str) -> IResult<&str, Vec<u32>> {
+fn seeds_parser<T: Value>(input: &str) -> IResult<&str, Vec<T>> {
     let (input, _) = tag("seeds: ")(input)?;
-    let (input, seeds) = separated_list1(space1, digit1_u32)(input)?;
+    let (input, seeds) = separated_list1(space1, digit1_num)(input)?;
     let (input, _) = many1(newline)(input)?;
 
     Ok((input, seeds))
 }
 
-fn map_item_parser(input: &str) -> IResult<&str, TableItem> {
-    let (input, data) = separated_list1(space1, digit1_u32)(input)?;
+fn map_item_parser<T: Value>(input: &str) -> IResult<&str, TableItem<T>> {
+    let (input, data) = separated_list1(space1, digit1_num)(input)?;
     let (input, _) = newline(input)?;
 
     Ok((input, (data[0], data[1], data[2])))
 }
 
-fn block_parser(input: &str) -> IResult<&str, MapBlock> {
-    let (input, _) = not_line_ending(input)?;
+fn block_parser<T: Value>(input: &str) -> IResult<&str, NamedBlock<'_, T>> {
+    let (input, source) = alpha1(input)?;
+    let (input, _) = tag("-to-")(input)?;
+    let (input, destination) = alpha1(input)?;
+    let (input, _) = tag(" map:")(input)?;
     let (input, _) = newline(input)?;
     let (input, data) = many1(map_item_parser)(input)?;
     let (input, _) = many0(newline)(input)?;
 
-    Ok((input, data))
+    Ok((input, (source, destination, data)))
 }
 
-fn map_parser(input: &str) -> IResult<&str, (Vec<u32>, Vec<MapBlock>)> {
+fn map_parser<T: Value>(input: &str) -> IResult<&str, (Vec<T>, Vec<NamedBlock<'_, T>>)> {
     let (input, seeds) = seeds_parser(input)?;
     let (input, blocks) = many1(block_parser)(input)?;
 
@@ -58,18 +85,22 @@ fn map_parser(input: &str) -> IResult<&str, (Vec<u32>, Vec<MapBlock>)> {
 
 // part one
 
-fn convert_item(item: u32, rule: TableItem) -> Option<u32> {
+// `Almanac` converts through its composed `Piecewise` map. The block by block
+// conversions below are only kept to check that map against.
+#[cfg(test)]
+fn convert_item<T: Value>(item: T, rule: TableItem<T>) -> Option<T> {
     let (dst_start, src_start, len) = rule;
 
-    // Trick to get around integer overflow
-    if item >= src_start && item - src_start < len {
-        Some(item - src_start + dst_start)
+    let offset = item.checked_sub(src_start)?;
+    if offset < len {
+        dst_start.checked_add(offset)
     } else {
         None
     }
 }
 
-fn apply_transform(mut seed: u32, blocks: &Vec<Vec<TableItem>>) -> u32 {
+#[cfg(test)]
+fn apply_transform<T: Value>(mut seed: T, blocks: &[MapBlock<T>]) -> T {
     for block in blocks {
         for rule in block {
             if let Some(res) = convert_item(seed, *rule) {
@@ -81,75 +112,76 @@ fn apply_transform(mut seed: u32, blocks: &Vec<Vec<TableItem>>) -> u32 {
     seed
 }
 
-fn proc_1(data: &str) -> u32 {
-    let (_, (seeds, blocks)) = map_parser(data).unwrap();
+fn proc_1(data: &str) -> u64 {
+    let almanac: Almanac = Almanac::parse(data).unwrap();
 
-    seeds
-        .into_iter()
-        .map(|s| apply_transform(s, &blocks))
+    almanac
+        .seeds
+        .iter()
+        .map(|&s| almanac.convert(s, "seed", "location").unwrap())
         .min()
         .unwrap()
 }
 
 // part two
 
-fn split_range(seed_range: SeedRange, table: &TableItem) -> (Option<SeedRange>, Vec<SeedRange>) {
-    let src_range = (table.1, table.1 + (table.2 - 1));
-    let is_intersect = seed_range.0 <= src_range.1 && seed_range.1 >= src_range.0;
-
-    let mut splits = vec![];
-    let intersect = if is_intersect {
-        if seed_range.0 < src_range.0 {
-            splits.push((seed_range.0, src_range.0 - 1));
-        }
-
-        if seed_range.1 > src_range.1 {
-            splits.push((src_range.1 + 1, seed_range.1));
-        }
-
-        Some((seed_range.0.max(src_range.0), seed_range.1.min(src_range.1)))
-    } else {
-        None
+// Splits `seeds` into the part `table` maps and the part it leaves alone.
+#[cfg(test)]
+fn split_range<T: Value>(
+    seeds: &IntervalSet<T>,
+    table: &TableItem<T>,
+) -> (IntervalSet<T>, IntervalSet<T>) {
+    let source = match table.1.range_end(table.2) {
+        Some(end) => IntervalSet::from_range(table.1, end),
+        None if table.2 == T::ZERO => IntervalSet::new(),
+        None => IntervalSet::from_range(table.1, T::MAX),
     };
 
-    (intersect, splits)
+    (seeds.intersection(&source), seeds.difference(&source))
 }
 
-fn convert_seed_range(seed_range: SeedRange, blocks: &Vec<Vec<TableItem>>) -> Vec<SeedRange> {
-    let mut ret = vec![seed_range];
+#[cfg(test)]
+fn convert_seed_range<T: Value>(
+    seed_range: SeedRange<T>,
+    blocks: &[MapBlock<T>],
+) -> Vec<SeedRange<T>> {
+    let mut ret = IntervalSet::from_range(seed_range.0, seed_range.1);
     for block in blocks {
-        let mut tmp = ret.clone();
-        ret = vec![];
-        while let Some(i) = tmp.pop() {
-            let mut split = false;
-            for rule in block {
-                if let (Some(intersect), splits) = split_range(i, rule) {
-                    ret.push((intersect.0 - rule.1 + rule.0, intersect.1 - rule.1 + rule.0));
-                    tmp.extend(splits);
-                    split = true;
-                    break;
-                }
-            }
-            if !split {
-                ret.push(i);
-            }
+        let mut mapped = IntervalSet::new();
+        for rule in block {
+            let (intersect, rest) = split_range(&ret, rule);
+            // `Almanac` rejects rules whose destination overflows.
+            let moved = intersect.shift(rule.1, rule.0).expect("rule maps past MAX");
+            mapped = mapped.union(&moved);
+            ret = rest;
         }
+        ret = ret.union(&mapped);
     }
-    ret
+    ret.ranges().to_vec()
 }
 
-fn proc_2(data: &str) -> u32 {
-    let (_, (seeds, blocks)) = map_parser(data).unwrap();
+fn proc_2(data: &str) -> u64 {
+    let almanac: Almanac = Almanac::parse(data).unwrap();
 
-    seeds
-        .chunks(2)
-        .map(|c| (c[0], c[0] + c[1] - 1))
-        .flat_map(|s| convert_seed_range(s, &blocks))
+    almanac
+        .seed_ranges()
+        .unwrap()
+        .into_iter()
+        .flat_map(|s| almanac.convert_range(s, "seed", "location").unwrap())
         .map(|r| r.0)
         .min()
         .unwrap()
 }
 
+fn proc_2_inverse(data: &str) -> u64 {
+    let almanac: Almanac = Almanac::parse(data).unwrap();
+    let seeds = almanac.seed_ranges().unwrap();
+
+    InverseAlmanac::new(almanac.chain("seed", "location").unwrap())
+        .lowest_location(&seeds)
+        .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,7 +191,7 @@ mod tests {
     fn test_seeds_parser() {
         let data = "seeds: 79 14 55 13\n\n";
 
-        let (input, seeds) = seeds_parser(data).unwrap();
+        let (input, seeds) = seeds_parser::<u64>(data).unwrap();
         assert!(input.is_empty());
         assert_eq!(seeds, vec![79, 14, 55, 13]);
     }
@@ -167,14 +199,14 @@ mod tests {
     #[test]
     fn test_block_parser() {
         let data = "seed-to-soil map:\n50 98 2\n52 50 48\n\n";
-        let (input, _) = block_parser(data).unwrap();
+        let (input, _) = block_parser::<u64>(data).unwrap();
         assert!(input.is_empty());
     }
 
     #[test]
     fn test_map_item_parser() {
         let data = "49 53 8\n";
-        let (input, values) = map_item_parser(data).unwrap();
+        let (input, values) = map_item_parser::<u64>(data).unwrap();
         assert_eq!(values, (49, 53, 8));
         assert!(input.is_empty());
     }
@@ -182,21 +214,28 @@ mod tests {
     #[test]
     fn test_map_parser() {
         let data = fs::read_to_string("data/test.txt").unwrap();
-        let (intput, _) = map_parser(&data).unwrap();
+        let (intput, _) = map_parser::<u64>(&data).unwrap();
         assert!(intput.is_empty());
     }
 
     #[test]
-    fn test_u32_parser() {
+    fn test_num_parser() {
         let data = "123";
-        let (intput, value) = digit1_u32(data).unwrap();
+        let (intput, value) = digit1_num::<u32>(data).unwrap();
         assert!(intput.is_empty());
         assert_eq!(value, 123);
+
+        assert_eq!(digit1_num::<u32>("4294967295"), Ok(("", u32::MAX)));
+        assert!(digit1_num::<u32>("4294967296").is_err());
+        assert_eq!(
+            digit1_num::<u64>("18446744073709551615"),
+            Ok(("", u64::MAX))
+        );
     }
 
     #[test]
     fn test_convert_item() {
-        let value = 79;
+        let value: u64 = 79;
         let table_1 = (50, 98, 2);
         let table_2 = (52, 50, 48);
         let res = convert_item(value, table_1);
@@ -204,16 +243,22 @@ mod tests {
         let res = convert_item(value, table_2);
         assert_eq!(res, Some(81));
 
-        let res = convert_item(69, (0, 69, 2));
+        let res = convert_item(69_u64, (0, 69, 2));
         assert_eq!(res, Some(0));
-        let res = convert_item(70, (0, 69, 2));
+        let res = convert_item(70_u64, (0, 69, 2));
         assert_eq!(res, Some(1));
 
-        let res = convert_item(71, (0, 69, 2));
+        let res = convert_item(71_u64, (0, 69, 2));
         assert_eq!(res, None);
 
-        let res = convert_item(68, (0, 69, 2));
+        let res = convert_item(68_u64, (0, 69, 2));
         assert_eq!(res, None);
+
+        assert_eq!(convert_item(u64::MAX, (0, u64::MAX, 1)), Some(0));
+        assert_eq!(convert_item(u64::MAX, (5, u64::MAX - 1, 2)), Some(6));
+        assert_eq!(convert_item(0, (u64::MAX, 0, 1)), Some(u64::MAX));
+        assert_eq!(convert_item(1, (u64::MAX, 0, 2)), None);
+        assert_eq!(convert_item(u32::MAX, (0, u32::MAX, 0)), None);
     }
 
     #[test]
@@ -228,95 +273,120 @@ mod tests {
         let data = fs::read_to_string("data/test.txt").unwrap();
         let res = proc_2(&data);
         assert_eq!(res, 46);
+        let res = proc_2_inverse(&data);
+        assert_eq!(res, 46);
+    }
+
+    fn split<T: Value>(
+        r: SeedRange<T>,
+        table: &TableItem<T>,
+    ) -> (Vec<SeedRange<T>>, Vec<SeedRange<T>>) {
+        let (intersect, rest) = split_range(&IntervalSet::from_range(r.0, r.1), table);
+        (intersect.ranges().to_vec(), rest.ranges().to_vec())
     }
 
     #[test]
     fn test_range_split() {
-        let r = (79, 79 + 14 - 1);
+        let r: SeedRange = (79, 79 + 14 - 1);
         let table = (50, 98, 2);
-        let res = split_range(r, &table);
-        assert_eq!(res, (None, vec![]));
+        let res = split(r, &table);
+        assert_eq!(res, (vec![], vec![(79, 92)]));
 
-        let r = (79, 79 + 14 - 1);
+        let r: SeedRange = (79, 79 + 14 - 1);
         let table = (52, 50, 48);
-        let res = split_range(r, &table);
-        assert_eq!(res, (Some((79, 92)), vec![]));
+        let res = split(r, &table);
+        assert_eq!(res, (vec![(79, 92)], vec![]));
 
-        let r = (55, 55 + 13 - 1);
+        let r: SeedRange = (55, 55 + 13 - 1);
         let table = (50, 98, 2);
-        let _res = split_range(r, &table);
+        let _res = split(r, &table);
 
         let table = (52, 50, 48);
-        let _res = split_range(r, &table);
+        let _res = split(r, &table);
 
         // 10    20
         //    15    24
-        let res = split_range((10, 20), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 20)), vec![(10, 14)]));
+        let res = split::<u64>((10, 20), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 20)], vec![(10, 14)]));
 
         // 10       25
         //    15 24
-        let res = split_range((10, 25), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 24)), vec![(10, 14), (25, 25)]));
+        let res = split::<u64>((10, 25), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 24)], vec![(10, 14), (25, 25)]));
 
         // 10    24
         //    15 24
-        let res = split_range((10, 24), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 24)), vec![(10, 14)]));
+        let res = split::<u64>((10, 24), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 24)], vec![(10, 14)]));
 
         // 15 24
         // 15 24
-        let res = split_range((15, 24), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 24)), vec![]));
+        let res = split::<u64>((15, 24), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 24)], vec![]));
 
         // 15 24
         // 15     34
-        let res = split_range((15, 24), &(0, 15, 20));
-        assert_eq!(res, (Some((15, 24)), vec![]));
+        let res = split::<u64>((15, 24), &(0, 15, 20));
+        assert_eq!(res, (vec![(15, 24)], vec![]));
 
         // 15    34
         // 15 24
-        let res = split_range((15, 34), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 24)), vec![(25, 34)]));
+        let res = split::<u64>((15, 34), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 24)], vec![(25, 34)]));
 
         // 15
         // 15 24
-        let res = split_range((15, 15), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 15)), vec![]));
+        let res = split::<u64>((15, 15), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 15)], vec![]));
 
         //    24
         // 15 24
-        let res = split_range((24, 24), &(0, 15, 10));
-        assert_eq!(res, (Some((24, 24)), vec![]));
+        let res = split::<u64>((24, 24), &(0, 15, 10));
+        assert_eq!(res, (vec![(24, 24)], vec![]));
 
         // 0       30
         //   15 24
-        let res = split_range((0, 30), &(0, 15, 10));
-        assert_eq!(res, (Some((15, 24)), vec![(0, 14), (25, 30)]));
+        let res = split::<u64>((0, 30), &(0, 15, 10));
+        assert_eq!(res, (vec![(15, 24)], vec![(0, 14), (25, 30)]));
+
+        let res = split((u64::MAX - 5, u64::MAX), &(0, u64::MAX - 2, 3));
+        assert_eq!(
+            res,
+            (
+                vec![(u64::MAX - 2, u64::MAX)],
+                vec![(u64::MAX - 5, u64::MAX - 3)]
+            )
+        );
+        let res = split((0, u32::MAX), &(0, 0, u32::MAX));
+        assert_eq!(res, (vec![(0, u32::MAX - 1)], vec![(u32::MAX, u32::MAX)]));
+        let res = split((0, u32::MAX), &(0, 0, 0));
+        assert_eq!(res, (vec![], vec![(0, u32::MAX)]));
     }
 
     #[test]
     fn test_sample() {
-        let r = (82, 82);
-        let res = convert_seed_range(r, &vec![vec![(50, 98, 2), (52, 50, 48)]]);
+        let r: SeedRange = (82, 82);
+        let res = convert_seed_range(r, &[vec![(50, 98, 2), (52, 50, 48)]]);
         assert_eq!(res, vec![(84, 84)]);
-        let res = convert_seed_range(res[0], &vec![vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)]]);
+        let res = convert_seed_range(res[0], &[vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)]]);
         assert_eq!(res, vec![(84, 84)]);
         let res = convert_seed_range(
             res[0],
-            &vec![vec![(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)]],
+            &[vec![(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)]],
         );
         assert_eq!(res, vec![(84, 84)]);
-        let res = convert_seed_range(res[0], &vec![vec![(88, 18, 7), (18, 25, 70)]]);
+        let res = convert_seed_range(res[0], &[vec![(88, 18, 7), (18, 25, 70)]]);
         assert_eq!(res, vec![(77, 77)]);
-        let res = convert_seed_range(
-            res[0],
-            &vec![vec![(45, 77, 23), (81, 45, 19), (68, 64, 13)]],
-        );
+        let res = convert_seed_range(res[0], &[vec![(45, 77, 23), (81, 45, 19), (68, 64, 13)]]);
         assert_eq!(res, vec![(45, 45)]);
-        let res = convert_seed_range(res[0], &vec![vec![(0, 69, 1), (1, 0, 69)]]);
+        let res = convert_seed_range(res[0], &[vec![(0, 69, 1), (1, 0, 69)]]);
         assert_eq!(res, vec![(46, 46)]);
-        let res = convert_seed_range((0, 9), &vec![vec![(20, 0, 5), (30, 5, 5)]]);
+        let res = convert_seed_range::<u64>((0, 9), &[vec![(20, 0, 5), (30, 5, 5)]]);
         assert_eq!(res, vec![(20, 24), (30, 34)]);
+
+        let res = convert_seed_range((u32::MAX - 9, u32::MAX), &[vec![(0, u32::MAX - 4, 5)]]);
+        assert_eq!(res, vec![(0, 4), (u32::MAX - 9, u32::MAX - 5)]);
+        let res = convert_seed_range((0, 1), &[vec![(u64::MAX - 1, 0, 2)]]);
+        assert_eq!(res, vec![(u64::MAX - 1, u64::MAX)]);
     }
 }
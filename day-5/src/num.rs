@@ -0,0 +1,49 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use interval_set::Endpoint;
+
+// Unsigned integer types the almanac can be built on. Offsets between values
+// are kept as i128, which holds the difference of any two of them.
+pub trait Value: Endpoint + Display + FromStr {
+    fn to_i128(self) -> i128;
+    fn from_i128(value: i128) -> Option<Self>;
+
+    // Last value of a range starting at `self` with `len` values, None when
+    // the range is empty or runs past MAX.
+    fn range_end(self, len: Self) -> Option<Self> {
+        self.checked_add(len.checked_sub(Self::ONE)?)
+    }
+}
+
+macro_rules! impl_value {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_value!(u8, u16, u32, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_end() {
+        assert_eq!(10_u64.range_end(5), Some(14));
+        assert_eq!(10_u64.range_end(0), None);
+        assert_eq!(u64::MAX.range_end(1), Some(u64::MAX));
+        assert_eq!(u64::MAX.range_end(2), None);
+        assert_eq!(u32::from_i128(1 << 32), None);
+    }
+}
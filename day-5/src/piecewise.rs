@@ -0,0 +1,264 @@
+use std::fmt;
+
+use interval_set::IntervalSet;
+
+use crate::num::Value;
+use crate::{MapBlock, SeedRange, TableItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<T = u64> {
+    pub start: T,
+    pub end: T,
+    pub offset: i128,
+}
+
+impl<T: Value> Segment<T> {
+    fn shift(&self, x: T) -> T {
+        T::from_i128(x.to_i128() + self.offset).expect("segment maps outside the domain")
+    }
+}
+
+// A map from T to T made of sorted, contiguous segments covering the whole
+// domain, each one shifting its values by a fixed offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piecewise<T = u64> {
+    pub segments: Vec<Segment<T>>,
+}
+
+impl<T: Value> Piecewise<T> {
+    pub fn identity() -> Self {
+        Piecewise {
+            segments: vec![Segment {
+                start: T::ZERO,
+                end: T::MAX,
+                offset: 0,
+            }],
+        }
+    }
+
+    // When rules overlap the first one wins, as in `apply_transform`. Rules
+    // are cut short where their source or destination would pass `T::MAX`,
+    // matching `convert_item`.
+    pub fn from_block(block: &MapBlock<T>) -> Self {
+        let domain_end = T::MAX.to_i128() + 1;
+        let rule_end = |r: &TableItem<T>| {
+            let (dst, src, len) = (r.0.to_i128(), r.1.to_i128(), r.2.to_i128());
+            src + len.min(domain_end - src).min(domain_end - dst)
+        };
+        let rules: Vec<&TableItem<T>> = block
+            .iter()
+            .filter(|r| rule_end(r) > r.1.to_i128())
+            .collect();
+
+        let mut bounds: Vec<i128> = vec![0, domain_end];
+        for r in &rules {
+            bounds.push(r.1.to_i128());
+            bounds.push(rule_end(r));
+        }
+        bounds.sort();
+        bounds.dedup();
+
+        let segments = bounds
+            .windows(2)
+            .map(|w| {
+                let offset = rules
+                    .iter()
+                    .find(|r| r.1.to_i128() <= w[0] && w[0] < rule_end(r))
+                    .map_or(0, |r| r.0.to_i128() - r.1.to_i128());
+                Segment {
+                    start: to_value(w[0]),
+                    end: to_value(w[1] - 1),
+                    offset,
+                }
+            })
+            .collect();
+
+        Self::merged(segments)
+    }
+
+    pub fn compose(blocks: &[MapBlock<T>]) -> Self {
+        blocks
+            .iter()
+            .fold(Self::identity(), |acc, b| acc.then(&Self::from_block(b)))
+    }
+
+    // Applies `self` first, then `next`.
+    pub fn then(&self, next: &Piecewise<T>) -> Piecewise<T> {
+        let mut segments = vec![];
+        for s in &self.segments {
+            for (lo, hi, n) in next.overlapping(s.shift(s.start), s.shift(s.end)) {
+                segments.push(Segment {
+                    start: to_value(lo.to_i128() - s.offset),
+                    end: to_value(hi.to_i128() - s.offset),
+                    offset: s.offset + n.offset,
+                });
+            }
+        }
+
+        Self::merged(segments)
+    }
+
+    fn merged(segments: Vec<Segment<T>>) -> Self {
+        let mut ret: Vec<Segment<T>> = vec![];
+        for s in segments {
+            match ret.last_mut() {
+                Some(last)
+                    if last.offset == s.offset && last.end.to_i128() + 1 == s.start.to_i128() =>
+                {
+                    last.end = s.end;
+                }
+                _ => ret.push(s),
+            }
+        }
+
+        Piecewise { segments: ret }
+    }
+
+    fn find(&self, x: T) -> usize {
+        self.segments.partition_point(|s| s.end < x)
+    }
+
+    // Parts of `lo..=hi` with the segment each part falls into.
+    fn overlapping(&self, lo: T, hi: T) -> impl Iterator<Item = (T, T, &Segment<T>)> + '_ {
+        self.segments[self.find(lo)..]
+            .iter()
+            .take_while(move |s| s.start <= hi)
+            .map(move |s| (s.start.max(lo), s.end.min(hi), s))
+    }
+
+    pub fn apply(&self, x: T) -> T {
+        self.segments[self.find(x)].shift(x)
+    }
+
+    // Values mapped into `range`. Images aren't sorted, so every segment is
+    // checked.
+    pub fn preimage(&self, range: SeedRange<T>) -> Vec<SeedRange<T>> {
+        self.segments
+            .iter()
+            .filter_map(|s| {
+                let lo = s.shift(s.start).to_i128().max(range.0.to_i128());
+                let hi = s.shift(s.end).to_i128().min(range.1.to_i128());
+                (lo <= hi).then(|| (to_value(lo - s.offset), to_value(hi - s.offset)))
+            })
+            .collect()
+    }
+
+    // Images of `range`, sorted and merged like `convert_seed_range` returns
+    // them.
+    pub fn apply_range(&self, range: SeedRange<T>) -> Vec<SeedRange<T>> {
+        self.overlapping(range.0, range.1)
+            .map(|(lo, hi, s)| (s.shift(lo), s.shift(hi)))
+            .collect::<IntervalSet<T>>()
+            .ranges()
+            .to_vec()
+    }
+}
+
+// Bounds computed in i128 always come from values of the domain.
+fn to_value<T: Value>(x: i128) -> T {
+    T::from_i128(x).expect("bound outside the domain")
+}
+
+impl<T: Value> fmt::Display for Piecewise<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in &self.segments {
+            writeln!(
+                f,
+                "{}..={} -> {}..={} ({:+})",
+                s.start,
+                s.end,
+                s.shift(s.start),
+                s.shift(s.end),
+                s.offset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::almanac::Almanac;
+    use crate::{apply_transform, convert_seed_range};
+    use std::fs;
+
+    #[test]
+    fn test_from_block() {
+        let p = Piecewise::<u32>::from_block(&vec![(50, 98, 2), (52, 50, 48)]);
+        assert_eq!(
+            p.segments,
+            [
+                Segment {
+                    start: 0,
+                    end: 49,
+                    offset: 0
+                },
+                Segment {
+                    start: 50,
+                    end: 97,
+                    offset: 2
+                },
+                Segment {
+                    start: 98,
+                    end: 99,
+                    offset: -48
+                },
+                Segment {
+                    start: 100,
+                    end: u32::MAX,
+                    offset: 0
+                },
+            ]
+        );
+        assert_eq!(p.apply(79), 81);
+        assert_eq!(p.apply(99), 51);
+        assert_eq!(p.apply(u32::MAX), u32::MAX);
+
+        let p = Piecewise::<u32>::from_block(&vec![(0, u32::MAX - 1, 2)]);
+        assert_eq!(p.apply(u32::MAX), 1);
+
+        // The part of the rule that would map past u64::MAX passes through.
+        let p = Piecewise::<u64>::from_block(&vec![(u64::MAX - 1, 10, 5)]);
+        assert_eq!(p.apply(10), u64::MAX - 1);
+        assert_eq!(p.apply(11), u64::MAX);
+        assert_eq!(p.apply(12), 12);
+        assert_eq!(p.segments.last().unwrap().end, u64::MAX);
+        assert_eq!(
+            p.preimage((u64::MAX, u64::MAX)),
+            [(11, 11), (u64::MAX, u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let data = fs::read_to_string("data/test.txt").unwrap();
+        let almanac: Almanac = Almanac::parse(&data).unwrap();
+        let composed = Piecewise::compose(&almanac.blocks);
+        assert_eq!(composed, almanac.composed);
+
+        for x in 0..200 {
+            assert_eq!(composed.apply(x), apply_transform(x, &almanac.blocks));
+        }
+
+        let lowest = almanac
+            .seed_ranges()
+            .unwrap()
+            .into_iter()
+            .flat_map(|r| composed.apply_range(r))
+            .map(|r| r.0)
+            .min();
+        assert_eq!(lowest, Some(46));
+
+        for r in [(79, 92), (0, 99), (55, 67), (0, u64::MAX)] {
+            assert_eq!(
+                composed.apply_range(r),
+                convert_seed_range(r, &almanac.blocks)
+            );
+        }
+
+        assert!(composed
+            .to_string()
+            .starts_with("0..=13 -> 22..=35 (+22)\n14..=14 -> 43..=43 (+29)\n"));
+    }
+}
@@ -1,22 +1,25 @@
+pub mod race;
+
 use nom::{
     bytes::complete::{tag, take_until1},
     character::complete::{digit1, newline, space1},
     multi::{many0, many1},
     IResult,
 };
+use race::{LinearCharge, RaceModel};
 use std::{fs, time::Instant};
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
 
-    let part_one = proc_1(&data);
+    let part_one = proc_1(&data, &LinearCharge);
     println!("Day 6 part one: {part_one}");
 
-    let part_two = proc_2(&data);
+    let part_two = proc_2(&data, &LinearCharge);
     println!("Day 6 part two: {part_two}");
 
     let now = Instant::now();
-    let part_two_brute_force = proc_2_brute_force(&data);
+    let part_two_brute_force = proc_2_brute_force(&data, &LinearCharge);
     println!("Day 6 part two (brute force): {part_two_brute_force}");
     println!("Elapsed: {:.2?}", now.elapsed());
 }
@@ -48,41 +51,69 @@ fn process_input_part_one(data: Vec<&str>) -> Vec<u64> {
         .collect::<Vec<u64>>()
 }
 
-fn calc(t: u64, d: u64) -> u32 {
-    let r1 = (t as f64) / 2.0 + ((t as f64).powf(2.0) - 4.0 * (d as f64)).sqrt() / 2.0;
-    let r2 = (t as f64) / 2.0 - ((t as f64).powf(2.0) - 4.0 * (d as f64)).sqrt() / 2.0;
-    let res = r1.ceil() - r2.floor() - 1.0;
-    res as u32
+fn wins(t: u64, d: u64, hold: u64) -> bool {
+    hold as u128 * (t - hold) as u128 > d as u128
+}
+
+// First and last hold times that beat `d` in a race of `t` ms. The winning
+// holds lie strictly between the roots of x^2 - t*x + d, which are found with
+// an integer square root and then nudged until the bound is exact. Distance
+// is symmetric around t/2, so the last hold mirrors the first.
+fn winning_holds(t: u64, d: u64) -> Option<(u64, u64)> {
+    let disc = (t as u128 * t as u128).checked_sub(4 * d as u128)?;
+    let root = disc.isqrt() as u64;
+
+    let mut first = (t - root) / 2;
+    while first > 0 && wins(t, d, first - 1) {
+        first -= 1;
+    }
+    while first <= t / 2 && !wins(t, d, first) {
+        first += 1;
+    }
+
+    (first <= t / 2).then(|| (first, t - first))
 }
 
-fn proc_1(data: &str) -> u32 {
+fn calc(model: &dyn RaceModel, t: u64, d: u64) -> u64 {
+    model
+        .winning_holds(t, d)
+        .map_or(0, |(first, last)| last - first + 1)
+}
+
+fn proc_1(data: &str, model: &dyn RaceModel) -> u64 {
     let (_, (d1, d2)) = parse(data).unwrap();
     let d1 = process_input_part_one(d1);
     let d2 = process_input_part_one(d2);
 
-    d1.into_iter().zip(d2).map(|(t, d)| calc(t, d)).product()
+    d1.into_iter()
+        .zip(d2)
+        .map(|(t, d)| calc(model, t, d))
+        .product()
 }
 
 fn process_input_part_two(data: Vec<&str>) -> u64 {
     data.join("").parse::<u64>().unwrap()
 }
 
-fn proc_2(data: &str) -> u32 {
+fn proc_2(data: &str, model: &dyn RaceModel) -> u64 {
     let (_, (d1, d2)) = parse(data).unwrap();
     let d1 = process_input_part_two(d1);
     let d2 = process_input_part_two(d2);
-    calc(d1, d2)
+    calc(model, d1, d2)
 }
 
-fn proc_2_brute_force(data: &str) -> u32 {
+fn proc_2_brute_force(data: &str, model: &dyn RaceModel) -> u32 {
     let (_, (d1, d2)) = parse(data).unwrap();
     let d1 = process_input_part_two(d1);
     let d2 = process_input_part_two(d2);
-    calc_brute_force(d1, d2)
+    calc_brute_force(model, d1, d2)
 }
 
-fn calc_brute_force(t: u64, d: u64) -> u32 {
-    (0..t).map(|x| t * x - x.pow(2)).filter(|x| x > &d).count() as u32
+fn calc_brute_force(model: &dyn RaceModel, t: u64, d: u64) -> u32 {
+    (0..t)
+        .map(|x| model.distance(t, x))
+        .filter(|&x| x > d as u128)
+        .count() as u32
 }
 
 #[cfg(test)]
@@ -100,7 +131,7 @@ mod tests {
     fn test_proc_1() {
         let data = fs::read_to_string("data/test.txt").unwrap();
 
-        let r = proc_1(&data);
+        let r = proc_1(&data, &LinearCharge);
         assert_eq!(r, 288);
     }
 
@@ -108,27 +139,57 @@ mod tests {
     fn test_proc_2() {
         let data = fs::read_to_string("data/test.txt").unwrap();
 
-        let r = proc_2(&data);
+        let r = proc_2(&data, &LinearCharge);
         assert_eq!(r, 71503);
     }
 
     #[test]
     fn test_calc() {
-        let res = calc(7, 9);
+        let res = calc(&LinearCharge, 7, 9);
         assert_eq!(res, 4);
-        let res = calc(15, 40);
+        let res = calc(&LinearCharge, 15, 40);
         assert_eq!(res, 8);
-        let res = calc(30, 200);
+        let res = calc(&LinearCharge, 30, 200);
         assert_eq!(res, 9);
     }
 
+    #[test]
+    fn test_winning_holds() {
+        assert_eq!(winning_holds(7, 9), Some((2, 5)));
+        assert_eq!(winning_holds(30, 200), Some((11, 19)));
+        assert_eq!(winning_holds(4, 3), Some((2, 2)));
+        assert_eq!(winning_holds(4, 4), None);
+        assert_eq!(winning_holds(0, 0), None);
+        assert_eq!(winning_holds(1, 0), None);
+        assert_eq!(winning_holds(u64::MAX, 0), Some((1, u64::MAX - 1)));
+        assert_eq!(winning_holds(u64::MAX, u64::MAX), Some((2, u64::MAX - 2)));
+
+        // Holding 15 ms ties the record exactly. The roots sit near 0 and
+        // 2^60, where f64 can't tell them apart from their neighbours.
+        let t = 1 << 60;
+        let res = winning_holds(t, 15 * (t - 15));
+        assert_eq!(res, Some((16, t - 16)));
+        assert_eq!(calc(&LinearCharge, t, 15 * (t - 15)), t - 31);
+
+        for t in 0..60 {
+            for d in 0..t * t / 4 + 2 {
+                let expected: Vec<u64> = (0..=t).filter(|&x| x * (t - x) > d).collect();
+                let res = winning_holds(t, d);
+                assert_eq!(
+                    res,
+                    expected.first().map(|&f| (f, *expected.last().unwrap()))
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_calc_brute_force() {
-        let res = calc_brute_force(7, 9);
+        let res = calc_brute_force(&LinearCharge, 7, 9);
         assert_eq!(res, 4);
-        let res = calc_brute_force(15, 40);
+        let res = calc_brute_force(&LinearCharge, 15, 40);
         assert_eq!(res, 8);
-        let res = calc_brute_force(30, 200);
+        let res = calc_brute_force(&LinearCharge, 30, 200);
         assert_eq!(res, 9);
     }
 }
@@ -0,0 +1,156 @@
+use crate::winning_holds;
+
+// How far a boat gets in a race of `time` ms after holding the button for
+// `hold` ms, and which holds beat a record distance.
+pub trait RaceModel {
+    fn distance(&self, time: u64, hold: u64) -> u128;
+
+    // First and last hold that beat `record`. The default binary searches
+    // for the best hold and then for each edge of the winning interval, so
+    // it assumes the distance rises to a single peak and then falls, without
+    // flat stretches on the way up.
+    fn winning_holds(&self, time: u64, record: u64) -> Option<(u64, u64)> {
+        let beats = |hold| self.distance(time, hold) > record as u128;
+
+        let peak = partition_point(0, time, |h| {
+            self.distance(time, h) < self.distance(time, h + 1)
+        });
+        if !beats(peak) {
+            return None;
+        }
+
+        let first = partition_point(0, peak, |h| !beats(h));
+        let last = partition_point(peak, time, beats) - 1;
+        Some((first, last))
+    }
+}
+
+// First value in `lo..hi` where `pred` stops holding, `hi` if it never does.
+// `pred` must hold for a prefix of the range and fail for the rest.
+fn partition_point(mut lo: u64, mut hi: u64, pred: impl Fn(u64) -> bool) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+// The puzzle's boat: every ms of holding adds 1 mm/ms of speed.
+pub struct LinearCharge;
+
+// Every ms of holding adds `factor` mm/ms of speed.
+pub struct ChargeMultiplier {
+    pub factor: u64,
+}
+
+// Speed grows by 1 mm/ms per ms of holding, up to `max_speed`.
+pub struct SpeedCap {
+    pub max_speed: u64,
+}
+
+// The boat loses `per_ms` mm/ms of speed for every ms it moves, and stops
+// once its speed reaches zero.
+pub struct Drag {
+    pub per_ms: u64,
+}
+
+impl RaceModel for LinearCharge {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        hold as u128 * (time - hold) as u128
+    }
+
+    fn winning_holds(&self, time: u64, record: u64) -> Option<(u64, u64)> {
+        winning_holds(time, record)
+    }
+}
+
+impl RaceModel for ChargeMultiplier {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        self.factor as u128 * hold as u128 * (time - hold) as u128
+    }
+
+    // factor * x > record exactly when x > record / factor, rounding down.
+    fn winning_holds(&self, time: u64, record: u64) -> Option<(u64, u64)> {
+        match self.factor {
+            0 => None,
+            factor => winning_holds(time, record / factor),
+        }
+    }
+}
+
+impl RaceModel for SpeedCap {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        hold.min(self.max_speed) as u128 * (time - hold) as u128
+    }
+}
+
+impl RaceModel for Drag {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        let remaining = (time - hold) as u128;
+        let (speed, drag) = (hold as u128, self.per_ms as u128);
+        let moving = match drag {
+            0 => remaining,
+            _ => remaining.min(speed.div_ceil(drag)),
+        };
+
+        moving * speed - drag * moving * moving.saturating_sub(1) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(model: &dyn RaceModel, time: u64, record: u64) -> Option<(u64, u64)> {
+        let wins: Vec<u64> = (0..=time)
+            .filter(|&h| model.distance(time, h) > record as u128)
+            .collect();
+        Some((*wins.first()?, *wins.last()?))
+    }
+
+    #[test]
+    fn test_models() {
+        let models: Vec<Box<dyn RaceModel>> = vec![
+            Box::new(LinearCharge),
+            Box::new(ChargeMultiplier { factor: 0 }),
+            Box::new(ChargeMultiplier { factor: 3 }),
+            Box::new(SpeedCap { max_speed: 1 }),
+            Box::new(SpeedCap { max_speed: 7 }),
+            Box::new(Drag { per_ms: 0 }),
+            Box::new(Drag { per_ms: 1 }),
+            Box::new(Drag { per_ms: 4 }),
+        ];
+
+        for model in &models {
+            for time in 0..40 {
+                for record in 0..time * time {
+                    assert_eq!(
+                        model.winning_holds(time, record),
+                        brute_force(model.as_ref(), time, record),
+                        "time {time}, record {record}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(ChargeMultiplier { factor: 2 }.distance(7, 3), 24);
+        assert_eq!(SpeedCap { max_speed: 2 }.distance(7, 3), 8);
+        // Speeds 5, 3, 1 and then stopped.
+        assert_eq!(Drag { per_ms: 2 }.distance(10, 5), 9);
+        assert_eq!(Drag { per_ms: 2 }.distance(7, 5), 8);
+
+        let model = SpeedCap { max_speed: 1000 };
+        assert_eq!(model.winning_holds(u64::MAX, 0), Some((1, u64::MAX - 1)));
+        assert_eq!(
+            model.winning_holds(1 << 40, 1000 * ((1 << 40) - 2000)),
+            Some((1000, 1999))
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Cards from weakest to strongest. A joker only appears once jacks have been
+// turned into jokers with `Hand::with_jokers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Card {
+    Joker,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Card {
+    pub fn from_char(c: char) -> Option<Card> {
+        let card = match c {
+            '2' => Card::Two,
+            '3' => Card::Three,
+            '4' => Card::Four,
+            '5' => Card::Five,
+            '6' => Card::Six,
+            '7' => Card::Seven,
+            '8' => Card::Eight,
+            '9' => Card::Nine,
+            'T' => Card::Ten,
+            'J' => Card::Jack,
+            'Q' => Card::Queen,
+            'K' => Card::King,
+            'A' => Card::Ace,
+            _ => return None,
+        };
+        Some(card)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::Ten => 'T',
+            Card::Jack | Card::Joker => 'J',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::Ace => 'A',
+        }
+    }
+}
+
+// Hand types from weakest to strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    // Jokers join whichever card the hand already holds most of, which is
+    // always the best use for them.
+    pub fn of(cards: &[Card; 5]) -> HandType {
+        let mut counts = [0_u8; 14];
+        let mut jokers = 0;
+        for &c in cards {
+            match c {
+                Card::Joker => jokers += 1,
+                _ => counts[c as usize] += 1,
+            }
+        }
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts[0] += jokers;
+
+        match (counts[0], counts[1]) {
+            (5, _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Hands compare by type first, then card by card from the left, so sorting
+// puts the weakest hand first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hand {
+    pub kind: HandType,
+    pub cards: [Card; 5],
+}
+
+impl Hand {
+    pub fn new(cards: [Card; 5]) -> Hand {
+        Hand {
+            kind: HandType::of(&cards),
+            cards,
+        }
+    }
+
+    // The part two rules: jacks become jokers, the weakest card but a
+    // wildcard when working out the hand type.
+    pub fn with_jokers(&self) -> Hand {
+        Hand::new(self.cards.map(|c| match c {
+            Card::Jack => Card::Joker,
+            c => c,
+        }))
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.cards {
+            write!(f, "{}", c.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseHandError {
+    Length(usize),
+    Card(char),
+}
+
+impl fmt::Display for ParseHandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHandError::Length(n) => write!(f, "A hand has 5 cards, found {n}"),
+            ParseHandError::Card(c) => write!(f, "Unknown card {c:?}"),
+        }
+    }
+}
+
+impl Error for ParseHandError {}
+
+impl FromStr for Hand {
+    type Err = ParseHandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = s
+            .chars()
+            .map(|c| Card::from_char(c).ok_or(ParseHandError::Card(c)))
+            .collect::<Result<Vec<Card>, _>>()?;
+        let cards: [Card; 5] = cards
+            .try_into()
+            .map_err(|v: Vec<Card>| ParseHandError::Length(v.len()))?;
+
+        Ok(Hand::new(cards))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hand_type() {
+        let kind = |s: &str| s.parse::<Hand>().unwrap().kind;
+        assert_eq!(kind("32T3K"), HandType::OnePair);
+        assert_eq!(kind("KK677"), HandType::TwoPair);
+        assert_eq!(kind("T55J5"), HandType::ThreeOfAKind);
+        assert_eq!(kind("23332"), HandType::FullHouse);
+        assert_eq!(kind("AA8AA"), HandType::FourOfAKind);
+        assert_eq!(kind("AAAAA"), HandType::FiveOfAKind);
+        assert_eq!(kind("23456"), HandType::HighCard);
+
+        let jokers = |s: &str| s.parse::<Hand>().unwrap().with_jokers().kind;
+        assert_eq!(jokers("T55J5"), HandType::FourOfAKind);
+        assert_eq!(jokers("KTJJT"), HandType::FourOfAKind);
+        assert_eq!(jokers("JJJJJ"), HandType::FiveOfAKind);
+        assert_eq!(jokers("2345J"), HandType::OnePair);
+        assert_eq!(jokers("2233J"), HandType::FullHouse);
+    }
+
+    #[test]
+    fn test_order() {
+        let hand = |s: &str| s.parse::<Hand>().unwrap();
+        assert!(hand("33332") > hand("2AAAA"));
+        assert!(hand("77888") > hand("77788"));
+        assert!(hand("KK677") > hand("KTJJT"));
+        assert!(hand("KTJJT").with_jokers() > hand("KK677").with_jokers());
+        assert!(hand("JKKK2").with_jokers() < hand("QQQQ2").with_jokers());
+    }
+
+    #[test]
+    fn test_parse_display() {
+        let hand: Hand = "T55J5".parse().unwrap();
+        assert_eq!(hand.to_string(), "T55J5");
+        assert_eq!(hand.with_jokers().to_string(), "T55J5");
+        assert_eq!(hand.kind.to_string(), "three of a kind");
+
+        assert_eq!("T55J".parse::<Hand>(), Err(ParseHandError::Length(4)));
+        assert_eq!("T55J55".parse::<Hand>(), Err(ParseHandError::Length(6)));
+        assert_eq!("T5xJ5".parse::<Hand>(), Err(ParseHandError::Card('x')));
+        assert_eq!(
+            "T5xJ5".parse::<Hand>().unwrap_err().to_string(),
+            "Unknown card 'x'"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use num::integer::gcd;
+use std::collections::HashSet;
+
+use crate::graph::Graph;
+
+// Path of a single ghost: after `tail` steps it enters a loop of `period`
+// steps over (node, direction index) states. Z hits before the loop are kept
+// in `tail_hits`, hits inside the first pass of the loop in `cycle_hits`.
+#[derive(Debug)]
+pub struct Cycle {
+    pub tail: u64,
+    pub period: u64,
+    pub tail_hits: Vec<u64>,
+    pub cycle_hits: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn hits_at(&self, step: u64) -> bool {
+        if step < self.tail {
+            self.tail_hits.contains(&step)
+        } else {
+            let offset = (step - self.tail) % self.period + self.tail;
+            self.cycle_hits.contains(&offset)
+        }
+    }
+
+    pub fn first_hit(&self) -> Option<u64> {
+        self.tail_hits
+            .iter()
+            .chain(self.cycle_hits.iter())
+            .copied()
+            .find(|&s| s > 0)
+            .or_else(|| self.cycle_hits.first().map(|&s| s + self.period))
+    }
+
+    // The LCM shortcut relies on every goal hit being a multiple of the
+    // period, which only holds for a single hit exactly one period in.
+    pub fn lcm_compatible(&self) -> bool {
+        self.tail_hits.is_empty() && self.cycle_hits == [self.period]
+    }
+}
+
+// Only states at direction index 0 are remembered: every loop passes through
+// one, so the reported tail may overshoot the true one by less than a full
+// pass of the directions, which the solver doesn't care about.
+pub fn find_cycle(
+    start: u32,
+    directions: &[char],
+    graph: &Graph,
+    is_goal: impl Fn(u32) -> bool,
+) -> Cycle {
+    let mut seen = vec![u64::MAX; graph.names.len()];
+    let mut hits = vec![];
+    let mut current = start;
+
+    for step in 0.. {
+        let idx = step as usize % directions.len();
+
+        if idx == 0 {
+            let first = seen[current as usize];
+            if first != u64::MAX {
+                let (tail_hits, cycle_hits) = hits.into_iter().partition(|&s| s < first);
+                return Cycle {
+                    tail: first,
+                    period: step - first,
+                    tail_hits,
+                    cycle_hits,
+                };
+            }
+            seen[current as usize] = step;
+        }
+
+        if is_goal(current) {
+            hits.push(step);
+        }
+
+        current = graph.next(current, directions[idx]);
+    }
+    unreachable!()
+}
+
+fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = ext_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+// Generalised CRT: merges x = a1 (mod m1) and x = a2 (mod m2) for moduli that
+// need not be coprime.
+pub fn crt(a1: u64, m1: u64, a2: u64, m2: u64) -> Option<(u64, u64)> {
+    let (a1, m1, a2, m2) = (a1 as i128, m1 as i128, a2 as i128, m2 as i128);
+    let (g, p, _) = ext_gcd(m1, m2);
+    let diff = a2 - a1;
+    if diff % g != 0 {
+        return None;
+    }
+
+    let m = (m1 / g).checked_mul(m2)?;
+    let k = (diff / g).checked_mul(p)?.rem_euclid(m2 / g);
+    let x = m1.checked_mul(k)?.checked_add(a1)?.rem_euclid(m);
+
+    Some((u64::try_from(x).ok()?, u64::try_from(m).ok()?))
+}
+
+fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+// Smallest step > 0 where every ghost stands on a goal node at the same time.
+// None if there is none, or if the periods' lcm doesn't fit in a u64, in
+// which case `crt` could no longer tell a missing solution from an overflow.
+pub fn solve(cycles: &[Cycle]) -> Option<u64> {
+    let max_tail = cycles.iter().map(|c| c.tail).max()?;
+
+    if let Some(step) = (1..max_tail).find(|&s| cycles.iter().all(|c| c.hits_at(s))) {
+        return Some(step);
+    }
+
+    cycles
+        .iter()
+        .try_fold(1, |acc, c| checked_lcm(acc, c.period))?;
+
+    let mut residues = vec![(0, 1)];
+    for cycle in cycles {
+        let mut next = HashSet::new();
+        for &(a, m) in &residues {
+            for &hit in &cycle.cycle_hits {
+                if let Some(r) = crt(a, m, hit % cycle.period, cycle.period) {
+                    next.insert(r);
+                }
+            }
+        }
+        residues = next.into_iter().collect();
+    }
+
+    let min_step = max_tail.max(1);
+    residues
+        .into_iter()
+        .filter_map(|(a, m)| {
+            if a >= min_step {
+                Some(a)
+            } else {
+                (min_step - a).div_ceil(m).checked_mul(m)?.checked_add(a)
+            }
+        })
+        .min()
+}
+
+// None when a ghost never hits a goal or the lcm doesn't fit in a u64.
+pub fn lcm_shortcut(cycles: &[Cycle]) -> Option<u64> {
+    cycles
+        .iter()
+        .try_fold(1, |acc, c| checked_lcm(acc, c.first_hit()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(1, 2, 2, 3), Some((5, 6)));
+        assert_eq!(crt(2, 4, 0, 6), Some((6, 12)));
+        assert_eq!(crt(1, 4, 0, 6), None);
+
+        let (p, q) = (u64::MAX - 58, u64::MAX - 82);
+        assert_eq!(crt(0, p, 0, q), None);
+        assert_eq!(crt(1, 1 << 32, 0, 3), Some(((1 << 33) + 1, 3 << 32)));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Two large primes: their lcm doesn't fit in a u64.
+        let cycle = |period: u64| Cycle {
+            tail: 0,
+            period,
+            tail_hits: vec![],
+            cycle_hits: vec![period],
+        };
+        let cycles = [cycle(u64::MAX - 58), cycle(u64::MAX - 82)];
+        assert_eq!(solve(&cycles), None);
+        assert_eq!(lcm_shortcut(&cycles), None);
+
+        let cycles = [cycle(1 << 40), cycle(3 << 20)];
+        assert_eq!(solve(&cycles), Some(3 << 40));
+        assert_eq!(lcm_shortcut(&cycles), Some(3 << 40));
+    }
+
+    #[test]
+    fn test_solve() {
+        let cycles = [
+            Cycle {
+                tail: 1,
+                period: 2,
+                tail_hits: vec![],
+                cycle_hits: vec![1],
+            },
+            Cycle {
+                tail: 0,
+                period: 3,
+                tail_hits: vec![],
+                cycle_hits: vec![2],
+            },
+        ];
+        assert_eq!(solve(&cycles), Some(5));
+        assert_eq!(lcm_shortcut(&cycles), Some(2));
+        assert!(!cycles[0].lcm_compatible());
+    }
+}
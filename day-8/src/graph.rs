@@ -0,0 +1,150 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+use crate::MapNode;
+
+#[derive(Debug, Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    // Returns false when the bit was already set.
+    pub fn insert(&mut self, idx: u32) -> bool {
+        let (word, bit) = (idx as usize / 64, idx % 64);
+        let was_set = self.words[word] & (1 << bit) != 0;
+        self.words[word] |= 1 << bit;
+        !was_set
+    }
+
+    pub fn contains(&self, idx: u32) -> bool {
+        self.words[idx as usize / 64] & (1 << (idx % 64)) != 0
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| (w * 64) as u32 + bit)
+        })
+    }
+}
+
+// Node names interned to dense ids in file order, with edges kept in flat
+// arrays indexed by id.
+#[derive(Debug)]
+pub struct Graph<'a> {
+    pub names: Vec<&'a str>,
+    pub ids: HashMap<&'a str, u32>,
+    pub left: Vec<u32>,
+    pub right: Vec<u32>,
+    pub starts: BitSet,
+    pub ends: BitSet,
+}
+
+impl<'a> Graph<'a> {
+    pub fn build(map_data: &[MapNode<'a>]) -> Result<Self> {
+        let mut ids = HashMap::new();
+        let mut names = vec![];
+
+        for m in map_data {
+            if ids.insert(m.0, names.len() as u32).is_some() {
+                bail!("Node {} defined twice", m.0);
+            }
+            names.push(m.0);
+        }
+
+        let lookup = |node: &str, edge: &str| {
+            ids.get(edge)
+                .copied()
+                .ok_or_else(|| anyhow!("Node {node} points to unknown node {edge}"))
+        };
+
+        let mut left = Vec::with_capacity(names.len());
+        let mut right = Vec::with_capacity(names.len());
+
+        for m in map_data {
+            left.push(lookup(m.0, m.1)?);
+            right.push(lookup(m.0, m.2)?);
+        }
+
+        let mut graph = Graph {
+            names,
+            ids,
+            left,
+            right,
+            starts: BitSet::new(0),
+            ends: BitSet::new(0),
+        };
+        graph.starts = graph.matching(|n| n.ends_with('A'));
+        graph.ends = graph.matching(|n| n.ends_with('Z'));
+
+        Ok(graph)
+    }
+
+    pub fn matching(&self, pred: impl Fn(&str) -> bool) -> BitSet {
+        let mut set = BitSet::new(self.names.len());
+        for (id, name) in self.names.iter().enumerate() {
+            if pred(name) {
+                set.insert(id as u32);
+            }
+        }
+
+        set
+    }
+
+    pub fn id(&self, name: &str) -> Result<u32> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown node {name}"))
+    }
+
+    pub fn name(&self, id: u32) -> &'a str {
+        self.names[id as usize]
+    }
+
+    pub fn next(&self, id: u32, lr: char) -> u32 {
+        if lr == 'L' {
+            self.left[id as usize]
+        } else {
+            self.right[id as usize]
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet::new(130);
+        assert!(set.insert(3));
+        assert!(set.insert(129));
+        assert!(!set.insert(3));
+        assert!(set.contains(129));
+        assert!(!set.contains(64));
+        assert_eq!(set.ones().collect::<Vec<_>>(), [3, 129]);
+    }
+
+    #[test]
+    fn test_build() {
+        let nodes = [("AAA", "BBZ", "AAA"), ("BBZ", "BBZ", "AAA")];
+        let graph = Graph::build(&nodes).unwrap();
+        assert_eq!(graph.names, ["AAA", "BBZ"]);
+        assert_eq!(graph.left, [1, 1]);
+        assert_eq!(graph.right, [0, 0]);
+        assert_eq!(graph.starts.ones().collect::<Vec<_>>(), [0]);
+        assert_eq!(graph.ends.ones().collect::<Vec<_>>(), [1]);
+
+        let nodes = [("AAA", "BBB", "AAA")];
+        assert!(Graph::build(&nodes).is_err());
+    }
+}
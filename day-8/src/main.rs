@@ -1,3 +1,10 @@
+pub mod cycle;
+pub mod graph;
+pub mod walk;
+
+use anyhow::{anyhow, bail, Result};
+use cycle::{find_cycle, lcm_shortcut, solve, Cycle};
+use graph::Graph;
 use nom::{
     bytes::complete::tag,
     character::complete::{alphanumeric1, newline, one_of},
@@ -5,19 +12,38 @@ use nom::{
     sequence::terminated,
     IResult,
 };
-use num::integer::lcm;
-use std::{collections::HashMap, fs};
+use std::{env, fs};
+use walk::{walk_all, NodePredicate};
 
 fn main() {
     let data = fs::read_to_string("data/input.txt").unwrap();
-    let part_one = proc_1(&data);
+    let part_one = proc_1(&data).unwrap();
     println!("Day 8 part one: {part_one}");
 
-    let part_two = proc_2(&data);
+    let (part_two, shortcut) = proc_2(&data).unwrap();
     println!("Day 8 part two: {part_two}");
+    if shortcut != Some(part_two) {
+        println!("Day 8 part two: LCM shortcut would give {shortcut:?}");
+    }
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let [start, goal] = args.as_slice() {
+        query(&data, start, goal).unwrap();
+    }
 }
 
-type MapNode<'a> = (&'a str, &'a str, &'a str);
+fn query(data: &str, start: &str, goal: &str) -> Result<()> {
+    let (_, (directions, nodes)) = parse(data).map_err(|e| e.to_owned())?;
+    let graph = build_map(nodes)?;
+
+    for walk in walk_all(&start.parse()?, &goal.parse()?, &directions, &graph)? {
+        println!("{} steps: {}", walk.steps, walk.path.join(" -> "));
+    }
+
+    Ok(())
+}
+
+pub type MapNode<'a> = (&'a str, &'a str, &'a str);
 
 fn parse_direction(input: &str) -> IResult<&str, Vec<char>> {
     let (input, data) = terminated(many1(one_of("LR")), newline)(input)?;
@@ -26,7 +52,7 @@ fn parse_direction(input: &str) -> IResult<&str, Vec<char>> {
     Ok((input, data))
 }
 
-fn parse_node(input: &str) -> IResult<&str, MapNode> {
+fn parse_node(input: &str) -> IResult<&str, MapNode<'_>> {
     let (input, node) = alphanumeric1(input)?;
     let (input, _) = tag(" = (")(input)?;
     let (input, edge_l) = alphanumeric1(input)?;
@@ -37,87 +63,72 @@ fn parse_node(input: &str) -> IResult<&str, MapNode> {
     Ok((input, (node, edge_l, edge_r)))
 }
 
-fn parse(input: &str) -> IResult<&str, (Vec<char>, Vec<MapNode>)> {
+fn parse(input: &str) -> IResult<&str, (Vec<char>, Vec<MapNode<'_>>)> {
     let (input, directions) = parse_direction(input)?;
     let (input, nodes) = many1(parse_node)(input)?;
 
     Ok((input, (directions, nodes)))
 }
 
-fn build_map(map_data: Vec<MapNode>) -> HashMap<&str, (&str, &str)> {
-    let mut map = HashMap::new();
-
-    for m in map_data {
-        map.insert(m.0, (m.1, m.2));
-    }
-
-    map
+fn build_map(map_data: Vec<MapNode<'_>>) -> Result<Graph<'_>> {
+    Graph::build(&map_data)
 }
 
 fn calc_1(
-    start_key: &str,
-    directions: &Vec<char>,
-    map: &HashMap<&str, (&str, &str)>,
-    part_one: bool,
-) -> u32 {
-    let mut curren_key = start_key;
-
-    for i in 0.. {
-        let idx = i % directions.len();
-        let lr = directions[idx];
-
-        curren_key = node_traverse(lr, curren_key, map);
-
-        if (part_one && curren_key == "ZZZ") || (!part_one && curren_key.ends_with('Z')) {
-            return i as u32 + 1;
-        }
+    start: &NodePredicate,
+    goal: &NodePredicate,
+    directions: &[char],
+    graph: &Graph,
+) -> Result<u32> {
+    let walks = walk_all(start, goal, directions, graph)?;
+    match walks.as_slice() {
+        [walk] => Ok(walk.steps as u32),
+        _ => bail!("Expected a single start node, found {}", walks.len()),
     }
-    unreachable!()
 }
 
-fn proc_1(data: &str) -> u32 {
-    let (_, (directions, nodes)) = parse(data).unwrap();
-    let map = build_map(nodes);
-    calc_1("AAA", &directions, &map, true)
+fn proc_1(data: &str) -> Result<u32> {
+    let (_, (directions, nodes)) = parse(data).map_err(|e| e.to_owned())?;
+    let graph = build_map(nodes)?;
+    calc_1(
+        &NodePredicate::Exact("AAA".to_string()),
+        &NodePredicate::Exact("ZZZ".to_string()),
+        &directions,
+        &graph,
+    )
 }
 
-fn find_starting_nodes<'a>(map: &HashMap<&'a str, (&'a str, &'a str)>) -> Vec<&'a str> {
-    let mut nodes = vec![];
-    for n in map.keys() {
-        if n.ends_with('A') {
-            nodes.push(*n);
-        }
-    }
+pub fn find_starting_nodes<'a>(graph: &Graph<'a>, start: &NodePredicate) -> Vec<&'a str> {
+    graph
+        .matching(|n| start.matches(n))
+        .ones()
+        .map(|id| graph.name(id))
+        .collect()
+}
 
-    nodes
+pub fn node_traverse<'a>(lr: char, node: &str, graph: &Graph<'a>) -> Result<&'a str> {
+    Ok(graph.name(graph.next(graph.id(node)?, lr)))
 }
 
-fn node_traverse<'a>(
-    lr: char,
-    node: &'a str,
-    map: &HashMap<&'a str, (&'a str, &'a str)>,
-) -> &'a str {
-    let current_node = map.get(node).unwrap();
-
-    if lr == 'L' {
-        current_node.0
-    } else {
-        current_node.1
-    }
+fn ghost_cycles(directions: &[char], graph: &Graph) -> Vec<Cycle> {
+    graph
+        .starts
+        .ones()
+        .map(|id| find_cycle(id, directions, graph, |n| graph.ends.contains(n)))
+        .collect()
 }
 
-fn calc_2(directions: Vec<char>, map: HashMap<&str, (&str, &str)>) -> u64 {
-    find_starting_nodes(&map)
-        .iter()
-        .map(|&n| calc_1(n, &directions, &map, false) as u64)
-        .reduce(lcm)
-        .unwrap()
+fn calc_2(directions: Vec<char>, graph: Graph) -> Result<(u64, Option<u64>)> {
+    let cycles = ghost_cycles(&directions, &graph);
+    let steps = solve(&cycles)
+        .ok_or_else(|| anyhow!("Ghosts never reach Z nodes together within u64::MAX steps"))?;
+    Ok((steps, lcm_shortcut(&cycles)))
 }
 
-fn proc_2(data: &str) -> u64 {
-    let (_, (directions, nodes)) = parse(data).unwrap();
-    let map = build_map(nodes);
-    calc_2(directions, map)
+fn proc_2(data: &str) -> Result<(u64, Option<u64>)> {
+    let (_, (directions, nodes)) = parse(data).map_err(|e| e.to_owned())?;
+    let graph = build_map(nodes)?;
+    calc_2(directions, graph)
 }
 
 #[cfg(test)]
@@ -130,15 +141,21 @@ mod test {
         let (input, (directions, nodes)) = parse(&data).unwrap();
         assert!(input.is_empty());
 
-        let map = build_map(nodes);
-        let res = calc_1("AAA", &directions, &map, true);
+        let graph = build_map(nodes).unwrap();
+        let res = calc_1(
+            &"AAA".parse().unwrap(),
+            &"ZZZ".parse().unwrap(),
+            &directions,
+            &graph,
+        )
+        .unwrap();
         dbg!(res);
     }
 
     #[test]
     fn test_parse_direction() {
         let data = "RL\n\n";
-        let (input, data) = parse_direction(&data).unwrap();
+        let (input, data) = parse_direction(data).unwrap();
         assert!(input.is_empty());
         assert_eq!(data, ['R', 'L']);
     }
@@ -146,7 +163,7 @@ mod test {
     #[test]
     fn test_parse_node() {
         let data = "AAA = (BBB, CCC)\n";
-        let (input, data) = parse_node(&data).unwrap();
+        let (input, data) = parse_node(data).unwrap();
         assert!(input.is_empty());
         assert_eq!(data.0, "AAA");
         assert_eq!(data.1, "BBB");
@@ -156,18 +173,54 @@ mod test {
     #[test]
     fn test_calc_1() {
         let data_1 = fs::read_to_string("data/test_1.txt").unwrap();
-        let res = proc_1(&data_1);
+        let res = proc_1(&data_1).unwrap();
         assert_eq!(res, 2);
 
         let data_2 = fs::read_to_string("data/test_2.txt").unwrap();
-        let res = proc_1(&data_2);
+        let res = proc_1(&data_2).unwrap();
         assert_eq!(res, 6);
     }
 
     #[test]
     fn test_calc_2() {
         let data = fs::read_to_string("data/test_3.txt").unwrap();
-        let res = proc_2(&data);
-        assert_eq!(res, 6);
+        let res = proc_2(&data).unwrap();
+        assert_eq!(res, (6, Some(6)));
+    }
+
+    #[test]
+    fn test_string_api() {
+        let data = fs::read_to_string("data/test_3.txt").unwrap();
+        let (_, (_, nodes)) = parse(&data).unwrap();
+        let graph = build_map(nodes).unwrap();
+
+        assert_eq!(
+            find_starting_nodes(&graph, &"*A".parse().unwrap()),
+            ["11A", "22A"]
+        );
+        assert_eq!(node_traverse('R', "11B", &graph).unwrap(), "11Z");
+        assert!(node_traverse('L', "33A", &graph).is_err());
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let data = "LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n";
+        assert!(proc_1(data).is_err());
+
+        let data = "L\n\nAAA = (BBB, BBB)\nZZZ = (ZZZ, ZZZ)\n";
+        assert!(proc_1(data).is_err());
+    }
+
+    #[test]
+    fn test_calc_2_offset_cycles() {
+        let data = fs::read_to_string("data/test_4.txt").unwrap();
+        let (_, (directions, nodes)) = parse(&data).unwrap();
+        let graph = build_map(nodes).unwrap();
+
+        let cycles = ghost_cycles(&directions, &graph);
+        assert!(cycles.iter().any(|c| !c.lcm_compatible()));
+
+        let res = calc_2(directions, graph).unwrap();
+        assert_eq!(res, (5, Some(2)));
     }
 }
@@ -0,0 +1,149 @@
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::str::FromStr;
+
+use crate::graph::{BitSet, Graph};
+
+#[derive(Debug, Clone)]
+pub enum NodePredicate {
+    Exact(String),
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+impl NodePredicate {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NodePredicate::Exact(s) => name == s,
+            NodePredicate::Prefix(s) => name.starts_with(s.as_str()),
+            NodePredicate::Suffix(s) => name.ends_with(s.as_str()),
+            NodePredicate::Regex(re) => re.is_match(name),
+        }
+    }
+}
+
+// "ZZZ" is an exact name, "*Z" a suffix, "A*" a prefix and "/re/" a regex.
+impl FromStr for NodePredicate {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(re) = s.strip_prefix('/').and_then(|r| r.strip_suffix('/')) {
+            Ok(NodePredicate::Regex(Regex::new(re)?))
+        } else if let Some(suffix) = s.strip_prefix('*') {
+            Ok(NodePredicate::Suffix(suffix.to_string()))
+        } else if let Some(prefix) = s.strip_suffix('*') {
+            Ok(NodePredicate::Prefix(prefix.to_string()))
+        } else if s.is_empty() {
+            bail!("Empty node predicate")
+        } else {
+            Ok(NodePredicate::Exact(s.to_string()))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Walk<'a> {
+    pub steps: u64,
+    pub path: Vec<&'a str>,
+}
+
+// Walks from `start` until a goal node is reached; the path includes both the
+// start and the goal node.
+pub fn walk<'a>(
+    start: u32,
+    directions: &[char],
+    graph: &Graph<'a>,
+    goals: &BitSet,
+) -> Result<Walk<'a>> {
+    let mut current = start;
+    let mut seen = BitSet::new(graph.names.len());
+    let mut path = vec![graph.name(start)];
+
+    for i in 0.. {
+        let idx = i as usize % directions.len();
+
+        if idx == 0 && !seen.insert(current) {
+            bail!("No target reachable from {}", graph.name(start));
+        }
+
+        current = graph.next(current, directions[idx]);
+        path.push(graph.name(current));
+
+        if goals.contains(current) {
+            return Ok(Walk { steps: i + 1, path });
+        }
+    }
+    unreachable!()
+}
+
+// One walk per node matching `start`, in file order.
+pub fn walk_all<'a>(
+    start: &NodePredicate,
+    goal: &NodePredicate,
+    directions: &[char],
+    graph: &Graph<'a>,
+) -> Result<Vec<Walk<'a>>> {
+    let starts = graph.matching(|n| start.matches(n));
+    let goals = graph.matching(|n| goal.matches(n));
+
+    starts
+        .ones()
+        .map(|id| walk(id, directions, graph, &goals))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_predicate() {
+        let exact: NodePredicate = "ZZZ".parse().unwrap();
+        assert!(exact.matches("ZZZ"));
+        assert!(!exact.matches("AZZZ"));
+
+        let suffix: NodePredicate = "*Z".parse().unwrap();
+        assert!(suffix.matches("11Z"));
+        assert!(!suffix.matches("Z11"));
+
+        let prefix: NodePredicate = "11*".parse().unwrap();
+        assert!(prefix.matches("11B"));
+        assert!(!prefix.matches("22B"));
+
+        let re: NodePredicate = "/^[12]{2}[AZ]$/".parse().unwrap();
+        assert!(re.matches("12Z"));
+        assert!(!re.matches("12B"));
+
+        assert!("".parse::<NodePredicate>().is_err());
+        assert!("/(/".parse::<NodePredicate>().is_err());
+    }
+
+    #[test]
+    fn test_walk_all() {
+        let nodes = [
+            ("AAA", "BBB", "BBB"),
+            ("BBB", "AAA", "ZZZ"),
+            ("ZZZ", "ZZZ", "ZZZ"),
+        ];
+        let graph = Graph::build(&nodes).unwrap();
+
+        let walks = walk_all(
+            &NodePredicate::Exact("AAA".into()),
+            &NodePredicate::Suffix("Z".into()),
+            &['L', 'R'],
+            &graph,
+        )
+        .unwrap();
+        assert_eq!(walks.len(), 1);
+        assert_eq!(walks[0].steps, 2);
+        assert_eq!(walks[0].path, ["AAA", "BBB", "ZZZ"]);
+
+        let walks = walk_all(
+            &NodePredicate::Prefix("B".into()),
+            &NodePredicate::Exact("ZZZ".into()),
+            &['L'],
+            &graph,
+        );
+        assert!(walks.is_err());
+    }
+}
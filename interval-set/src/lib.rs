@@ -0,0 +1,245 @@
+use std::fmt::Debug;
+use std::ops::{Add, Sub};
+
+// Unsigned integer types usable as interval ends. Plain `+` and `-` are only
+// used where the operands are known to stay in range.
+pub trait Endpoint:
+    Copy + Ord + Debug + Into<u128> + Add<Output = Self> + Sub<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_endpoint {
+    ($($t:ty),*) => {
+        $(
+            impl Endpoint for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_endpoint!(u8, u16, u32, u64);
+
+// A set of values stored as inclusive `(start, end)` ranges. The ranges are
+// kept sorted, disjoint and with a gap between neighbours, so two sets holding
+// the same values compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Endpoint> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+
+    // `start..=end`, empty when `start > end`.
+    pub fn from_range(start: T, end: T) -> Self {
+        Self::from_ranges([(start, end)])
+    }
+
+    // Sorts the ranges and merges the ones that overlap or touch. Ranges with
+    // `start > end` are dropped.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = ranges.into_iter().filter(|r| r.0 <= r.1).collect();
+        ranges.sort();
+
+        let mut ret: Vec<(T, T)> = vec![];
+        for r in ranges {
+            match ret.last_mut() {
+                Some(last) if last.1.checked_add(T::ONE).is_none_or(|next| r.0 <= next) => {
+                    last.1 = last.1.max(r.1);
+                }
+                _ => ret.push(r),
+            }
+        }
+
+        IntervalSet { ranges: ret }
+    }
+
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Number of values in the set. u128 holds even the full u64 range.
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|&(lo, hi)| hi.into() - lo.into() + 1)
+            .sum()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.0)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.ranges.partition_point(|r| r.1 < x);
+        self.ranges.get(i).is_some_and(|r| r.0 <= x)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.ranges.iter().chain(&other.ranges).copied())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (a, b) = (&self.ranges, &other.ranges);
+        let (mut i, mut j) = (0, 0);
+        let mut ret = vec![];
+
+        while i < a.len() && j < b.len() {
+            let lo = a[i].0.max(b[j].0);
+            let hi = a[i].1.min(b[j].1);
+            if lo <= hi {
+                ret.push((lo, hi));
+            }
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { ranges: ret }
+    }
+
+    // Values of `self` that aren't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let b = &other.ranges;
+        let mut j = 0;
+        let mut ret = vec![];
+
+        for &(mut lo, hi) in &self.ranges {
+            while j < b.len() && b[j].1 < lo {
+                j += 1;
+            }
+
+            let mut covered = false;
+            for &(b_lo, b_hi) in b[j..].iter().take_while(|r| r.0 <= hi) {
+                if b_lo > lo {
+                    ret.push((lo, b_lo - T::ONE));
+                }
+                if b_hi >= hi {
+                    covered = true;
+                    break;
+                }
+                lo = b_hi + T::ONE;
+            }
+            if !covered {
+                ret.push((lo, hi));
+            }
+        }
+
+        IntervalSet { ranges: ret }
+    }
+
+    // Moves every value by `to - from`, so `from` lands on `to`. None when a
+    // value would leave the range of `T`.
+    pub fn shift(&self, from: T, to: T) -> Option<Self> {
+        let moved = |x: T| {
+            if to >= from {
+                x.checked_add(to - from)
+            } else {
+                x.checked_sub(from - to)
+            }
+        };
+
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|&(lo, hi)| Some((moved(lo)?, moved(hi)?)))
+            .collect::<Option<_>>()?;
+        Some(IntervalSet { ranges })
+    }
+}
+
+impl<T: Endpoint> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise() {
+        let set = IntervalSet::from_ranges([(10_u32, 20), (21, 25), (5, 12), (30, 29), (40, 40)]);
+        assert_eq!(set.ranges(), [(5, 25), (40, 40)]);
+        assert_eq!(set.len(), 22);
+        assert_eq!(set.min(), Some(5));
+        assert!(set.contains(25) && set.contains(40));
+        assert!(!set.contains(26) && !set.contains(41) && !set.contains(4));
+
+        let set = IntervalSet::from_ranges([(0_u8, u8::MAX), (3, 3)]);
+        assert_eq!(set.ranges(), [(0, u8::MAX)]);
+        assert_eq!(set.len(), 256);
+        assert_eq!(IntervalSet::from_range(0, u64::MAX).len(), 1 << 64);
+        assert!(IntervalSet::<u32>::new().is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = IntervalSet::from_ranges([(0_u32, 9), (20, 29)]);
+        let b = IntervalSet::from_ranges([(5_u32, 24), (29, 40)]);
+
+        assert_eq!(a.union(&b).ranges(), [(0, 40)]);
+        assert_eq!(a.intersection(&b).ranges(), [(5, 9), (20, 24), (29, 29)]);
+        assert_eq!(a.difference(&b).ranges(), [(0, 4), (25, 28)]);
+        assert_eq!(b.difference(&a).ranges(), [(10, 19), (30, 40)]);
+        assert!(a.difference(&a).is_empty());
+
+        // 0       30
+        //   15 24
+        let a = IntervalSet::from_range(0_u32, 30);
+        let b = IntervalSet::from_range(15, 24);
+        assert_eq!(a.difference(&b).ranges(), [(0, 14), (25, 30)]);
+
+        let all = IntervalSet::from_range(0, u64::MAX);
+        let top = IntervalSet::from_range(u64::MAX - 1, u64::MAX);
+        assert_eq!(all.difference(&top).ranges(), [(0, u64::MAX - 2)]);
+        assert_eq!(top.difference(&all), IntervalSet::new());
+        assert_eq!(all.intersection(&top), top);
+    }
+
+    #[test]
+    fn test_shift() {
+        let set = IntervalSet::from_ranges([(98_u32, 99), (50, 60)]);
+        assert_eq!(set.shift(98, 50).unwrap().ranges(), [(2, 12), (50, 51)]);
+        assert_eq!(set.shift(50, 52).unwrap().ranges(), [(52, 62), (100, 101)]);
+        assert_eq!(set.shift(50, 0).unwrap().min(), Some(0));
+        assert_eq!(set.shift(51, 0), None);
+        assert_eq!(
+            set.shift(99, u32::MAX).unwrap().ranges(),
+            [(u32::MAX - 49, u32::MAX - 39), (u32::MAX - 1, u32::MAX)]
+        );
+        assert_eq!(set.shift(98, u32::MAX), None);
+    }
+}